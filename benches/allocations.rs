@@ -0,0 +1,45 @@
+use json_parser::parse;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+// counts allocations and reallocations, the latter being what growing a Vec costs
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// Parses a large flat array of numbers and an array of same-shaped records, printing the time and
+// the number of allocations parse_str made for each.
+fn main() {
+    let numbers = format!("[{}]", (0..1_000_000).map(|i| (i * 7 % 1000).to_string()).collect::<Vec<_>>().join(","));
+    let record = r#"{"id":1,"name":"x","active":true,"score":1.5,"tags":["a","b","c","d","e","f"],"x":1,"y":2,"z":3}"#;
+    let records = format!("[{}]", vec![record; 100_000].join(","));
+    for (name, input) in [("numbers", &numbers), ("records", &records)] {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let value = parse::parse_str(input).unwrap();
+        let elapsed = start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        drop(value);
+        println!("{:>8}: {:>9} bytes, {:>10.3?}, {:>8} allocations", name, input.len(), elapsed, allocations);
+    }
+}
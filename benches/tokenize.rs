@@ -0,0 +1,19 @@
+use json_parser::parse;
+use std::time::Instant;
+
+// Times parse_str on growing inputs; with linear tokenizing the time per byte stays flat.
+fn main() {
+    let record = std::fs::read_to_string("tree.json").unwrap();
+    for copies in [1, 10, 100, 1000] {
+        let input = format!("[{}]", vec![record.as_str(); copies].join(","));
+        let start = Instant::now();
+        parse::parse_str(&input).unwrap();
+        let elapsed = start.elapsed();
+        println!(
+            "{:>9} bytes: {:>10.3?} ({:.1} ns/byte)",
+            input.len(),
+            elapsed,
+            elapsed.as_nanos() as f64 / input.len() as f64
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use json_parser::parse::{self, JsonValue, Visitor};
+
+// Re-serializes a document, writing "[redacted]" in place of whatever is stored under a
+// "password" key, however deeply it's nested.
+struct Redactor {
+    out: String,
+    // one entry per open container, true once it has written an element
+    separated: Vec<bool>,
+    // a key was just written, so the value after it needs no comma
+    after_key: bool,
+    // the next value belongs to a "password" key
+    redact_next: bool,
+    // how many containers deep we are inside a redacted value
+    skipping: usize,
+}
+
+impl Redactor {
+    fn separate(&mut self) {
+        if let Some(separated) = self.separated.last_mut() {
+            if *separated {
+                self.out.push(',');
+            }
+            *separated = true;
+        }
+    }
+
+    // writes what goes before a value; false if the value itself shouldn't be written
+    fn begin(&mut self) -> bool {
+        if self.skipping > 0 {
+            return false;
+        }
+        if self.after_key {
+            self.after_key = false;
+        } else {
+            self.separate();
+        }
+        if self.redact_next {
+            self.redact_next = false;
+            self.out.push_str("\"[redacted]\"");
+            return false;
+        }
+        true
+    }
+
+    fn write(&mut self, value: &JsonValue) {
+        if self.begin() {
+            self.out.push_str(&value.to_string());
+        }
+    }
+
+    fn start(&mut self, open: char) {
+        if self.begin() {
+            self.out.push(open);
+            self.separated.push(false);
+        } else {
+            self.skipping += 1;
+        }
+    }
+
+    fn end(&mut self, close: char) {
+        if self.skipping > 0 {
+            self.skipping -= 1;
+        } else {
+            self.separated.pop();
+            self.out.push(close);
+        }
+    }
+}
+
+impl Visitor for Redactor {
+    fn visit_null(&mut self) {
+        self.write(&JsonValue::Null);
+    }
+
+    fn visit_bool(&mut self, b: bool) {
+        self.write(&JsonValue::Bool(b));
+    }
+
+    fn visit_number(&mut self, number: &JsonValue) {
+        self.write(number);
+    }
+
+    fn visit_string(&mut self, s: &str) {
+        self.write(&JsonValue::from(s));
+    }
+
+    fn start_array(&mut self, _len: usize) {
+        self.start('[');
+    }
+
+    fn end_array(&mut self) {
+        self.end(']');
+    }
+
+    fn start_object(&mut self, _len: usize) {
+        self.start('{');
+    }
+
+    fn visit_key(&mut self, key: &str) {
+        if self.skipping > 0 {
+            return;
+        }
+        self.separate();
+        self.out.push_str(&JsonValue::from(key).to_string());
+        self.out.push(':');
+        self.after_key = true;
+        self.redact_next = key == "password";
+    }
+
+    fn end_object(&mut self) {
+        self.end('}');
+    }
+}
+
+fn main() {
+    let input = r#"{
+        "user": "ada",
+        "password": "hunter2",
+        "accounts": [
+            {"name": "mail", "password": {"hash": "x1", "salt": "y2"}},
+            {"name": "bank", "pin": 1234, "password": 42}
+        ]
+    }"#;
+    let value = parse::parse_str(input).expect("example input is valid");
+    let mut redactor = Redactor { out: String::new(), separated: Vec::new(), after_key: false, redact_next: false, skipping: 0 };
+    value.visit(&mut redactor);
+    println!("{}", redactor.out);
+}
@@ -1,6 +1,6 @@
 pub mod parse {
     #[derive(Debug, PartialEq, Clone)]
-    enum JsonToken {
+    pub enum JsonToken {
         LeftBrace,
         RightBrace,
         LeftBracket,
@@ -9,219 +9,2949 @@ pub mod parse {
         Comma,
         String(String),
         Number(f64),
+        Integer(i64),
+        UInteger(u64),
+        // only produced with ParserOptions::preserve_number_text
+        RawNumber(String),
         True,
         False,
         Null,
     }
     
     #[derive(Debug)]
-    enum TokenizeError {
-        UnexpectedCharacter(char, u32),
+    pub enum TokenizeError {
+        UnexpectedCharacter { ch: char, line: usize, column: usize },
+        InvalidEscape { ch: char, line: usize, column: usize },
+        InvalidUnicodeEscape { line: usize, column: usize },
+        UnpairedSurrogate { line: usize, column: usize },
+        UnclosedString { start: usize, line: usize, column: usize },
+        MalformedNumber { line: usize, column: usize },
+        LeadingZero { line: usize, column: usize },
+        UnclosedComment { line: usize, column: usize },
+        NumberOutOfRange { line: usize, column: usize },
+        ControlCharacter { ch: char, line: usize, column: usize },
+        // `position` is the byte offset where the input ran out
+        UnexpectedEndOfInput { position: usize, line: usize, column: usize },
         // Add more error variants as needed
     }
     
-    struct JsonTokenizer<'a> {
+    use std::fmt;
+    use std::ops::Range;
+    impl fmt::Display for JsonToken {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                JsonToken::LeftBrace => write!(f, "{{"),
+                JsonToken::RightBrace => write!(f, "}}"),
+                JsonToken::LeftBracket => write!(f, "["),
+                JsonToken::RightBracket => write!(f, "]"),
+                JsonToken::Colon => write!(f, ":"),
+                JsonToken::Comma => write!(f, ","),
+                JsonToken::String(s) => write!(f, "{:?}", s),
+                JsonToken::Number(num) => write!(f, "{}", num),
+                JsonToken::Integer(num) => write!(f, "{}", num),
+                JsonToken::UInteger(num) => write!(f, "{}", num),
+                JsonToken::RawNumber(text) => f.write_str(text),
+                JsonToken::True => write!(f, "true"),
+                JsonToken::False => write!(f, "false"),
+                JsonToken::Null => write!(f, "null"),
+            }
+        }
+    }
+    
+    impl fmt::Display for TokenizeError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                // NUL and other control bytes mostly mean binary data, so name them plainly
+                TokenizeError::UnexpectedCharacter { ch, line, column } if ch.is_control() => {
+                    write!(f, "unexpected control character U+{:04X} at line {}, column {}", *ch as u32, line, column)
+                },
+                TokenizeError::UnexpectedCharacter { ch, line, column } => write!(f, "unexpected character '{}' at line {}, column {}", ch.escape_debug(), line, column),
+                TokenizeError::InvalidEscape { ch, line, column } => write!(f, "invalid escape sequence '\\{}' at line {}, column {}", ch, line, column),
+                TokenizeError::InvalidUnicodeEscape { line, column } => write!(f, "invalid unicode escape at line {}, column {}", line, column),
+                TokenizeError::UnpairedSurrogate { line, column } => write!(f, "unpaired surrogate in unicode escape at line {}, column {}", line, column),
+                TokenizeError::UnclosedString { line, column, .. } => write!(f, "unclosed string starting at line {}, column {}", line, column),
+                TokenizeError::MalformedNumber { line, column } => write!(f, "malformed number at line {}, column {}", line, column),
+                TokenizeError::LeadingZero { line, column } => write!(f, "number with a leading zero at line {}, column {}", line, column),
+                TokenizeError::UnclosedComment { line, column } => write!(f, "unclosed block comment starting at line {}, column {}", line, column),
+                TokenizeError::NumberOutOfRange { line, column } => write!(f, "number out of range at line {}, column {}", line, column),
+                TokenizeError::ControlCharacter { ch, line, column } => write!(f, "unescaped control character U+{:04X} in string at line {}, column {}", *ch as u32, line, column),
+                TokenizeError::UnexpectedEndOfInput { line, column, .. } => write!(f, "unexpected end of input at line {}, column {}", line, column),
+            }
+        }
+    }
+    
+    impl std::error::Error for TokenizeError {}
+    
+    pub struct JsonTokenizer<'a> {
         input: &'a str,
         position: usize,
+        options: ParserOptions,
+        // leave strings without escapes empty, for callers that slice them from the source
+        skip_plain_strings: bool,
+    }
+    
+    fn line_column(input: &str, position: usize) -> (usize, usize) {
+        // 1-based line and column of the character at byte offset `position`
+        let (mut line, mut column) = (1, 1);
+        for ch in input[..position].chars() {
+            if ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+    
+    fn is_whitespace(ch: char) -> bool {
+        // JSON only knows these four, unlike char::is_whitespace
+        matches!(ch, ' ' | '\t' | '\n' | '\r')
     }
     
     impl<'a> JsonTokenizer<'a> {
-        fn new(input: &'a str) -> Self {
-            JsonTokenizer { input, position: 0 }
+        pub fn new(input: &'a str) -> Self {
+            JsonTokenizer::with_options(input, ParserOptions::default())
         }
     
+        pub fn with_options(input: &'a str, options: ParserOptions) -> Self {
+            JsonTokenizer { input, position: 0, options, skip_plain_strings: false }
+        }
+    
+        // `position` is a byte offset into `input`, so stepping back over a character
+        // has to go by its utf-8 length
         fn next(&mut self) -> Option<char> {
-            self.position += 1;
-            self.input.chars().nth(self.position - 1)
+            let ch = self.input[self.position..].chars().next()?;
+            self.position += ch.len_utf8();
+            Some(ch)
+        }
+    
+        fn peek(&self) -> Option<char> {
+            self.input[self.position..].chars().next()
+        }
+    
+        fn location(&self, position: usize) -> (usize, usize) {
+            line_column(self.input, position)
+        }
+    
+        fn unexpected_end(&self) -> TokenizeError {
+            let (line, column) = self.location(self.position);
+            TokenizeError::UnexpectedEndOfInput { position: self.position, line, column }
+        }
+    
+        fn last_location(&self) -> (usize, usize) {
+            // location of the character most recently returned by next()
+            let start = self.input[..self.position].char_indices().next_back().map_or(0, |(i, _)| i);
+            self.location(start)
         }
     
         fn parse_int(&mut self) -> Result<JsonToken, TokenizeError> {
             let start_position = self.position;
-            while let Some(ch) = self.next() {
-                if !ch.is_ascii_digit() && ch != '.' {
-                    self.position -= 1; // Move the position back for the next token to start at the non-numeric character
-                    let number_str = &self.input[start_position..(self.position)];
-                    if let Ok(number) = number_str.parse::<f64>() {
-                        return Ok(JsonToken::Number(number));
-                    } else {
-                        return Err(TokenizeError::UnexpectedCharacter(ch, self.position.try_into().unwrap()));
+            if self.peek() == Some('-') {
+                self.position += 1;
+            }
+            if self.peek() == Some('0') {
+                self.position += 1;
+                if self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+                    let (line, column) = self.last_location();
+                    return Err(TokenizeError::LeadingZero { line, column });
+                }
+                self.position -= 1;
+            }
+            let mut prev = '\0';
+            let mut seen_dot = false;
+            // the number runs up to the first character that can't be part of it, or the end of input
+            while let Some(ch) = self.peek() {
+                let exponent_sign = (ch == '+' || ch == '-') && (prev == 'e' || prev == 'E');
+                if !ch.is_ascii_digit() && ch != '.' && ch != 'e' && ch != 'E' && !exponent_sign {
+                    break;
+                }
+                if prev == '.' && !ch.is_ascii_digit() {
+                    break;
+                }
+                self.position += 1;
+                if ch == '.' {
+                    // a dot needs a digit on both sides, so `.5` and `5..` are malformed here
+                    if seen_dot || !prev.is_ascii_digit() {
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::MalformedNumber { line, column });
                     }
+                    seen_dot = true;
                 }
+                prev = ch;
+            }
+            if prev == '.' {
+                // nothing but the dot after the last digit, as in `5.` or `1.e3`
+                let (line, column) = self.last_location();
+                return Err(TokenizeError::MalformedNumber { line, column });
             }
     
-            Err(TokenizeError::UnexpectedCharacter('\0', self.position.try_into().unwrap()))
+            let number_str = &self.input[start_position..self.position];
+            if !number_str.contains(['.', 'e', 'E']) {
+                if let Ok(number) = number_str.parse::<i64>() {
+                    return Ok(JsonToken::Integer(number));
+                }
+                // too big for i64 but may still fit without going through f64
+                if let Ok(number) = number_str.parse::<u64>() {
+                    return Ok(JsonToken::UInteger(number));
+                }
+            }
+            match number_str.parse::<f64>() {
+                // f64 parsing saturates to infinity, which plain JSON can't express
+                Ok(number) if !number.is_finite() && !self.options.allow_nan_infinity => {
+                    let (line, column) = self.location(start_position);
+                    Err(TokenizeError::NumberOutOfRange { line, column })
+                },
+                Ok(number) => Ok(JsonToken::Number(number)),
+                Err(_) => {
+                    let (line, column) = self.location(start_position);
+                    Err(TokenizeError::MalformedNumber { line, column })
+                },
+            }
         }
     
     
-        fn parse_string(&mut self) -> Result<JsonToken, TokenizeError> {
+        fn parse_string(&mut self, quote: char) -> Result<JsonToken, TokenizeError> {
+            let start = self.position - 1; // the opening quote was already consumed
             let mut string = String::new();
+            let mut escaped = false;
             while let Some(ch) = self.next() {
-                // TODO: sanitize escaped characters
                 match ch {
-                    '"' => return Ok(JsonToken::String(string)),
+                    ch if ch == quote => return Ok(JsonToken::String(string)),
+                    '\\' => {
+                        if self.skip_plain_strings && !escaped {
+                            string.push_str(&self.input[start + 1..self.position - 1]);
+                        }
+                        escaped = true;
+                        string.push(self.parse_escape()?)
+                    },
+                    '\u{0}'..='\u{1f}' if !self.options.allow_control_characters => {
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::ControlCharacter { ch, line, column });
+                    },
+                    _ if self.skip_plain_strings && !escaped => {},
                     _ => string.push(ch)
                 }
             }
-            // TODO: add different error for unclosed strings
-            Err(TokenizeError::UnexpectedCharacter('"', self.position.try_into().unwrap()))
+            let (line, column) = self.location(start);
+            Err(TokenizeError::UnclosedString { start, line, column })
+        }
+    
+    
+        fn parse_escape(&mut self) -> Result<char, TokenizeError> {
+            // called right after a backslash inside a string
+            match self.next() {
+                Some('"') => Ok('"'),
+                Some('\'') if self.options.allow_single_quotes => Ok('\''),
+                Some('\\') => Ok('\\'),
+                Some('/') => Ok('/'),
+                Some('b') => Ok('\u{8}'),
+                Some('f') => Ok('\u{c}'),
+                Some('n') => Ok('\n'),
+                Some('r') => Ok('\r'),
+                Some('t') => Ok('\t'),
+                Some('u') => self.parse_unicode_escape(),
+                Some(ch) => {
+                    let (line, column) = self.last_location();
+                    Err(TokenizeError::InvalidEscape { ch, line, column })
+                },
+                None => {
+                    Err(self.unexpected_end())
+                },
+            }
+        }
+    
+    
+        fn parse_hex4(&mut self) -> Result<u32, TokenizeError> {
+            let mut code: u32 = 0;
+            for _ in 0..4 {
+                match self.next().and_then(|ch| ch.to_digit(16)) {
+                    Some(digit) => code = code * 16 + digit,
+                    None => {
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::InvalidUnicodeEscape { line, column });
+                    },
+                }
+            }
+            Ok(code)
+        }
+    
+    
+        fn parse_unicode_escape(&mut self) -> Result<char, TokenizeError> {
+            // \uXXXX, called right after the 'u'
+            let code = self.parse_hex4()?;
+            let code = match code {
+                0xD800..=0xDBFF => {
+                    // high surrogate, has to be followed by an escaped low surrogate
+                    if self.next() != Some('\\') || self.next() != Some('u') {
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::UnpairedSurrogate { line, column });
+                    }
+                    let low = self.parse_hex4()?;
+                    if !(0xDC00..=0xDFFF).contains(&low) {
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::UnpairedSurrogate { line, column });
+                    }
+                    0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                },
+                0xDC00..=0xDFFF => {
+                    let (line, column) = self.last_location();
+                    return Err(TokenizeError::UnpairedSurrogate { line, column });
+                },
+                _ => code,
+            };
+            let (line, column) = self.last_location();
+            char::from_u32(code).ok_or(TokenizeError::InvalidUnicodeEscape { line, column })
         }
     
     
         fn parse_keyword(&mut self, keyword: &'static str, token: JsonToken) -> Result<JsonToken, TokenizeError> {
             // tokenize keywords (true, false, null)
             self.position -= 1;
-            let start_position = self.position;
     
             for expected_ch in keyword.chars() {
                 if let Some(ch) = self.next() {
                     if ch != expected_ch {
-                        return Err(TokenizeError::UnexpectedCharacter(ch, self.position.try_into().unwrap()));
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::UnexpectedCharacter { ch, line, column });
                     }
                 } else {
-                    return Err(TokenizeError::UnexpectedCharacter('\0', self.position.try_into().unwrap()));
+                    return Err(self.unexpected_end());
                 }
             }
+            // `nullable` or `truex` aren't a keyword followed by something else; any other
+            // character is left for whatever reads next, so parse_prefix can stop right here
+            match self.peek() {
+                Some(ch) if ch.is_alphanumeric() || ch == '_' => {
+                    let (line, column) = self.location(self.position);
+                    Err(TokenizeError::UnexpectedCharacter { ch, line, column })
+                },
+                _ => Ok(token),
+            }
+        }
+    
+        fn skip_comment(&mut self) -> Result<(), TokenizeError> {
+            // called right after the first '/'
+            let (line, column) = self.last_location();
+            match self.next() {
+                Some('/') => {
+                    while let Some(ch) = self.next() {
+                        if ch == '\n' {
+                            break;
+                        }
+                    }
+                    Ok(())
+                },
+                Some('*') => {
+                    let mut prev = '\0';
+                    while let Some(ch) = self.next() {
+                        if prev == '*' && ch == '/' {
+                            return Ok(());
+                        }
+                        prev = ch;
+                    }
+                    Err(TokenizeError::UnclosedComment { line, column })
+                },
+                Some(ch) => {
+                    let (line, column) = self.last_location();
+                    Err(TokenizeError::UnexpectedCharacter { ch, line, column })
+                },
+                None => {
+                    Err(self.unexpected_end())
+                },
+            }
+        }
+    
+        pub fn tokenize(&mut self) -> Result<Vec<JsonToken>, TokenizeError>{
+            Ok(self.tokenize_with_spans()?.into_iter().map(|(token, _)| token).collect())
+        }
     
-            Ok(token)
+        /// Like `tokenize`, but pairs every token with the byte range it covers in the input.
+        pub fn tokenize_with_spans(&mut self) -> Result<Vec<(JsonToken, Range<usize>)>, TokenizeError> {
+            let mut tokens = Vec::new();
+            while let Some(token) = self.next_token()? {
+                tokens.push(token);
+            }
+            Ok(tokens)
         }
     
-        fn tokenize(&mut self) -> Result<Vec<JsonToken>, TokenizeError>{
-            let mut tokens: Vec<JsonToken> = Vec::new();
+        // the next token and its span, skipping whitespace and comments; None at the end of input
+        fn next_token(&mut self) -> Result<Option<(JsonToken, Range<usize>)>, TokenizeError> {
             while let Some(ch) = self.next() {
-                match ch {
-                    '{' => tokens.push(JsonToken::LeftBrace),
-                    '}' => tokens.push(JsonToken::RightBrace),
-                    ',' => tokens.push(JsonToken::Comma),
-                    ':' => tokens.push(JsonToken::Colon),
-                    '[' => tokens.push(JsonToken::LeftBracket),
-                    ']' => tokens.push(JsonToken::RightBracket),
-                    '0'..='9' => {
+                let start = self.position - ch.len_utf8();
+                let token = match ch {
+                    '{' => JsonToken::LeftBrace,
+                    '}' => JsonToken::RightBrace,
+                    ',' => JsonToken::Comma,
+                    ':' => JsonToken::Colon,
+                    '[' => JsonToken::LeftBracket,
+                    ']' => JsonToken::RightBracket,
+                    '-' if self.options.allow_nan_infinity && self.peek() == Some('I') => {
+                        self.next();
+                        self.parse_keyword("Infinity", JsonToken::Number(f64::NEG_INFINITY))?
+                    },
+                    'N' if self.options.allow_nan_infinity => self.parse_keyword("NaN", JsonToken::Number(f64::NAN))?,
+                    'I' if self.options.allow_nan_infinity => self.parse_keyword("Infinity", JsonToken::Number(f64::INFINITY))?,
+                    '-' | '.' | '0'..='9' => {
                         self.position -= 1; // parse_int jumping back to the first character of number;
-                        tokens.push(self.parse_int()?)
+                        let number = self.parse_int()?;
+                        if self.options.preserve_number_text {
+                            JsonToken::RawNumber(self.input[start..self.position].to_string())
+                        } else {
+                            number
+                        }
                     },
-                    '"' => tokens.push(self.parse_string()?),
-                    't' => tokens.push(self.parse_keyword("true", JsonToken::True)?),
-                    'f' => tokens.push(self.parse_keyword("false", JsonToken::False)?),
-                    'n' => tokens.push(self.parse_keyword("null", JsonToken::Null)?),
-                    _ => {}
-                }
+                    '"' => self.parse_string('"')?,
+                    '\'' if self.options.allow_single_quotes => self.parse_string('\'')?,
+                    't' => self.parse_keyword("true", JsonToken::True)?,
+                    'f' => self.parse_keyword("false", JsonToken::False)?,
+                    'n' => self.parse_keyword("null", JsonToken::Null)?,
+                    ch if is_whitespace(ch) => continue,
+                    '/' if self.options.allow_comments => {
+                        self.skip_comment()?;
+                        continue;
+                    },
+                    _ => {
+                        let (line, column) = self.last_location();
+                        return Err(TokenizeError::UnexpectedCharacter { ch, line, column });
+                    },
+                };
+                return Ok(Some((token, start..self.position)));
             }
-            Ok(tokens)
+            Ok(None)
         }
     }
     
+    pub fn tokenize(input: &str) -> Result<Vec<JsonToken>, TokenizeError> {
+        JsonTokenizer::new(input).tokenize()
+    }
     
-    #[derive(Debug)]
+    /// Tokens with the byte range each one covers in `input`, e.g. for highlighting or linting.
+    pub fn tokenize_with_spans(input: &str) -> Result<Vec<(JsonToken, Range<usize>)>, TokenizeError> {
+        JsonTokenizer::new(input).tokenize_with_spans()
+    }
+    
+    
+    use std::borrow::Cow;
+    use std::collections::{HashMap, HashSet};
+    #[derive(Debug, Clone)]
     pub enum JsonValue {
         Null,
         Bool(bool),
         Number(f64),
+        Integer(i64),
+        /// Only used for integers above `i64::MAX`, everything else stays `Integer`.
+        UInteger(u64),
+        /// A number exactly as it was written, see `ParserOptions::preserve_number_text`.
+        RawNumber(String),
         String(String),
         Array(Vec<JsonValue>),
+        /// Entries stay in source order, duplicates included, and every serializer writes
+        /// them back out in that order.
         Object(Vec<(String, JsonValue)>)
     }
     
-    #[derive(Debug)]
-    enum ParseError {
-        UnexpectedToken(JsonToken),
-        UnexpectedEnd,
+    /// Objects compare equal when they hold the same key/value pairs, regardless of order.
+    impl PartialEq for JsonValue {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (JsonValue::Null, JsonValue::Null) => true,
+                (JsonValue::Bool(a), JsonValue::Bool(b)) => a == b,
+                (JsonValue::Number(a), JsonValue::Number(b)) => a == b,
+                (JsonValue::Integer(a), JsonValue::Integer(b)) => a == b,
+                (JsonValue::UInteger(a), JsonValue::UInteger(b)) => a == b,
+                (JsonValue::RawNumber(a), JsonValue::RawNumber(b)) => a == b,
+                (JsonValue::String(a), JsonValue::String(b)) => a == b,
+                (JsonValue::Array(a), JsonValue::Array(b)) => a == b,
+                (JsonValue::Object(a), JsonValue::Object(b)) => objects_equal(a, b),
+                _ => false,
+            }
+        }
     }
     
-    struct JsonParser<'a> {
-        tokens: &'a [JsonToken],
-        position: usize,
+    // every entry has to pair up with a distinct entry of the other object, in any order;
+    // shared by JsonValue and BorrowedValue
+    fn objects_equal<K: Ord, V: PartialEq>(a: &[(K, V)], b: &[(K, V)]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        // the usual case, both sides keep their keys in the same order
+        if a.iter().zip(b).all(|(x, y)| x.0 == y.0) {
+            match a.iter().zip(b).position(|(x, y)| x.1 != y.1) {
+                None => return true,
+                // only another entry under the same key could pair up instead
+                Some(i) if a.iter().filter(|entry| entry.0 == a[i].0).count() == 1 => return false,
+                Some(_) => {},
+            }
+        }
+    
+        let mut left: Vec<&(K, V)> = a.iter().collect();
+        let mut right: Vec<&(K, V)> = b.iter().collect();
+        left.sort_by(|x, y| x.0.cmp(&y.0));
+        right.sort_by(|x, y| x.0.cmp(&y.0));
+        if left.iter().zip(&right).any(|(x, y)| x.0 != y.0) {
+            return false;
+        }
+        // entries under a duplicated key may pair up in any order among themselves
+        left.chunk_by(|x, y| x.0 == y.0).zip(right.chunk_by(|x, y| x.0 == y.0)).all(|(group, others)| {
+            let mut matched = vec![false; others.len()];
+            group.iter().all(|entry| {
+                match others.iter().enumerate().position(|(i, other)| !matched[i] && entry.1 == other.1) {
+                    Some(i) => {
+                        matched[i] = true;
+                        true
+                    },
+                    None => false,
+                }
+            })
+        })
     }
     
-    impl<'a> JsonParser<'a> {
-        fn new(tokens: &'a [JsonToken]) -> Self {
-            JsonParser { tokens, position: 0 }
+    // comparisons with plain values, as in `assert_eq!(value["active"], true)`
+    impl PartialEq<bool> for JsonValue {
+        fn eq(&self, other: &bool) -> bool {
+            self.as_bool() == Some(*other)
         }
+    }
     
-        fn next(&mut self) -> Option<&'a JsonToken> {
-            let token = self.tokens.get(self.position);
-            self.position += 1;
-            token
+    // any numeric variant, so a parsed `2` equals `2.0` here even though it's an `Integer`
+    impl PartialEq<f64> for JsonValue {
+        fn eq(&self, other: &f64) -> bool {
+            self.as_f64() == Some(*other)
         }
+    }
     
-        fn parse(&mut self) -> Result<JsonValue, ParseError> {
-            if let Some(token) = self.next() {
-                match token {
-                    JsonToken::Null => Ok(JsonValue::Null),
-                    JsonToken::True => Ok(JsonValue::Bool(true)),
-                    JsonToken::False => Ok(JsonValue::Bool(false)),
-                    JsonToken::Number(num) => Ok(JsonValue::Number(*num)),
-                    JsonToken::String(s) => Ok(JsonValue::String(s.clone())),
-                    JsonToken::LeftBrace => self.parse_object(),
-                    JsonToken::LeftBracket => self.parse_array(),
-                    _ => Err(ParseError::UnexpectedToken(token.clone())),
-                }
-            } else {
-                Err(ParseError::UnexpectedEnd)
+    impl PartialEq<str> for JsonValue {
+        fn eq(&self, other: &str) -> bool {
+            self.as_str() == Some(other)
+        }
+    }
+    
+    impl PartialEq<&str> for JsonValue {
+        fn eq(&self, other: &&str) -> bool {
+            self.as_str() == Some(*other)
+        }
+    }
+    
+    impl JsonValue {
+        pub fn as_i64(&self) -> Option<i64> {
+            match self {
+                JsonValue::Integer(num) => Some(*num),
+                // only floats holding an exact integer in range convert
+                JsonValue::Number(num) if num.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(num) => Some(*num as i64),
+                JsonValue::RawNumber(text) => text.parse().ok().or_else(|| JsonValue::Number(text.parse().ok()?).as_i64()),
+                _ => None,
             }
         }
     
-        fn parse_object(&mut self) -> Result<JsonValue, ParseError> {
-            let mut object = Vec::new();
+        pub fn as_u64(&self) -> Option<u64> {
+            match self {
+                JsonValue::UInteger(num) => Some(*num),
+                JsonValue::Integer(num) => u64::try_from(*num).ok(),
+                JsonValue::Number(num) if num.fract() == 0.0 && (0.0..u64::MAX as f64).contains(num) => Some(*num as u64),
+                JsonValue::RawNumber(text) => text.parse().ok().or_else(|| JsonValue::Number(text.parse().ok()?).as_u64()),
+                _ => None,
+            }
+        }
     
-            loop {
-                if let Some(token) = self.next() {
-                    match token {
-                        JsonToken::RightBrace => return Ok(JsonValue::Object(object)),
-                        JsonToken::String(key) => {
-                            if let Some(JsonToken::Colon) = self.next() {
-                                let value = self.parse()?;
-                                object.push((key.clone(), value));
-    
-                                match self.next() {
-                                    Some(JsonToken::Comma) => continue,
-                                    Some(JsonToken::RightBrace) => return Ok(JsonValue::Object(object)),
-                                    _ => return Err(ParseError::UnexpectedToken(token.clone())),
-                                }
-                            } else {
-                                return Err(ParseError::UnexpectedToken(token.clone()));
-                            }
+        pub fn as_f64(&self) -> Option<f64> {
+            match self {
+                JsonValue::Number(num) => Some(*num),
+                JsonValue::Integer(num) => Some(*num as f64),
+                JsonValue::UInteger(num) => Some(*num as f64),
+                JsonValue::RawNumber(text) => text.parse().ok(),
+                _ => None,
+            }
+        }
+    
+        /// The exact text of a `RawNumber`, e.g. to hand to a decimal type; `as_f64` gives
+        /// the nearest `f64` of the same value.
+        pub fn as_number_str(&self) -> Option<&str> {
+            match self {
+                JsonValue::RawNumber(text) => Some(text),
+                _ => None,
+            }
+        }
+    
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                JsonValue::String(s) => Some(s),
+                _ => None,
+            }
+        }
+    
+        pub fn as_bool(&self) -> Option<bool> {
+            match self {
+                JsonValue::Bool(b) => Some(*b),
+                _ => None,
+            }
+        }
+    
+        pub fn as_array(&self) -> Option<&[JsonValue]> {
+            match self {
+                JsonValue::Array(array) => Some(array),
+                _ => None,
+            }
+        }
+    
+        pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+            match self {
+                JsonValue::Object(object) => Some(object),
+                _ => None,
+            }
+        }
+    
+        /// Moves the elements out of an array without cloning them.
+        pub fn into_array(self) -> Option<Vec<JsonValue>> {
+            match self {
+                JsonValue::Array(array) => Some(array),
+                _ => None,
+            }
+        }
+    
+        /// Moves the entries out of an object, in source order.
+        pub fn into_object(self) -> Option<Vec<(String, JsonValue)>> {
+            match self {
+                JsonValue::Object(object) => Some(object),
+                _ => None,
+            }
+        }
+    
+        pub fn value_type(&self) -> JsonType {
+            match self {
+                JsonValue::Null => JsonType::Null,
+                JsonValue::Bool(_) => JsonType::Bool,
+                JsonValue::Number(_) | JsonValue::Integer(_) | JsonValue::UInteger(_) | JsonValue::RawNumber(_) => JsonType::Number,
+                JsonValue::String(_) => JsonType::String,
+                JsonValue::Array(_) => JsonType::Array,
+                JsonValue::Object(_) => JsonType::Object,
+            }
+        }
+    
+        pub fn is_null(&self) -> bool {
+            matches!(self, JsonValue::Null)
+        }
+    
+        pub fn is_bool(&self) -> bool {
+            matches!(self, JsonValue::Bool(_))
+        }
+    
+        /// True for every numeric variant, integers and raw numbers included.
+        pub fn is_number(&self) -> bool {
+            matches!(self, JsonValue::Number(_) | JsonValue::Integer(_) | JsonValue::UInteger(_) | JsonValue::RawNumber(_))
+        }
+    
+        pub fn is_string(&self) -> bool {
+            matches!(self, JsonValue::String(_))
+        }
+    
+        pub fn is_array(&self) -> bool {
+            matches!(self, JsonValue::Array(_))
+        }
+    
+        pub fn is_object(&self) -> bool {
+            matches!(self, JsonValue::Object(_))
+        }
+    
+        /// Number of elements of an array or entries of an object; `None` for scalars.
+        pub fn len(&self) -> Option<usize> {
+            match self {
+                JsonValue::Array(array) => Some(array.len()),
+                JsonValue::Object(object) => Some(object.len()),
+                _ => None,
+            }
+        }
+    
+        pub fn is_empty(&self) -> Option<bool> {
+            self.len().map(|len| len == 0)
+        }
+    
+        /// Key/value pairs of an object in document order; empty for anything else.
+        pub fn entries(&self) -> impl Iterator<Item = (&str, &JsonValue)> {
+            self.as_object().unwrap_or(&[]).iter().map(|(key, value)| (key.as_str(), value))
+        }
+    
+        /// Elements of an array; empty for anything else.
+        pub fn elements(&self) -> impl Iterator<Item = &JsonValue> {
+            self.as_array().unwrap_or(&[]).iter()
+        }
+    
+        /// Collects an object into a `HashMap`; with duplicate keys the last one wins,
+        /// unlike `get` which returns the first.
+        pub fn into_map(self) -> Option<HashMap<String, JsonValue>> {
+            match self {
+                JsonValue::Object(object) => Some(object.into_iter().collect()),
+                _ => None,
+            }
+        }
+    
+        /// Borrowing version of `into_map`, with the same last-key-wins rule.
+        pub fn to_map(&self) -> Option<HashMap<&str, &JsonValue>> {
+            match self {
+                JsonValue::Object(object) => Some(object.iter().map(|(key, value)| (key.as_str(), value)).collect()),
+                _ => None,
+            }
+        }
+    
+        /// Looser than `==`: objects compare as maps, so a duplicate key only counts with its
+        /// last value (as in `to_map`), and numbers compare by value, so `1` equals `1.0`.
+        pub fn semantic_eq(&self, other: &JsonValue) -> bool {
+            match (self, other) {
+                (JsonValue::Array(a), JsonValue::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.semantic_eq(y)),
+                (JsonValue::Object(_), JsonValue::Object(_)) => {
+                    let (a, b) = (self.to_map().unwrap(), other.to_map().unwrap());
+                    a.len() == b.len() && a.iter().all(|(key, value)| b.get(key).is_some_and(|other| value.semantic_eq(other)))
+                },
+                _ => match (integer_value(self), integer_value(other)) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => match (self.as_f64(), other.as_f64()) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => self == other,
+                    },
+                },
+            }
+        }
+    
+        /// Deep-merges `other` into `self`: keys of two objects merge recursively, new keys are
+        /// appended, and anything else (arrays included) is replaced by `other`.
+        pub fn merge(&mut self, other: JsonValue) {
+            self.merge_values(other, false);
+        }
+    
+        /// Like `merge`, but two arrays are concatenated instead of replaced.
+        pub fn merge_concat(&mut self, other: JsonValue) {
+            self.merge_values(other, true);
+        }
+    
+        fn merge_values(&mut self, other: JsonValue, concat_arrays: bool) {
+            match (self, other) {
+                (JsonValue::Object(object), JsonValue::Object(other)) => {
+                    for (key, value) in other {
+                        match object.iter_mut().find(|(k, _)| *k == key) {
+                            Some((_, existing)) => existing.merge_values(value, concat_arrays),
+                            None => object.push((key, value)),
                         }
-                        _ => return Err(ParseError::UnexpectedToken(token.clone())),
                     }
-                } else {
-                    return Err(ParseError::UnexpectedEnd);
-                }
+                },
+                (JsonValue::Array(array), JsonValue::Array(other)) if concat_arrays => array.extend(other),
+                (this, other) => *this = other,
             }
         }
     
-        fn parse_array(&mut self) -> Result<JsonValue, ParseError> {
-            let mut array = Vec::new();
+        /// Resolves an RFC 6901 JSON Pointer such as `/users/0/name`; `""` points at `self`.
+        pub fn pointer(&self, path: &str) -> Option<&JsonValue> {
+            if path.is_empty() {
+                return Some(self);
+            }
+            let mut current = self;
+            for segment in path.strip_prefix('/')?.split('/') {
+                let segment = unescape_pointer_segment(segment);
+                current = match current {
+                    JsonValue::Object(_) => current.get(&segment)?,
+                    JsonValue::Array(_) => current.get_index(parse_pointer_index(&segment)?)?,
+                    _ => return None,
+                };
+            }
+            Some(current)
+        }
     
-            loop {
-                if let Some(token) = self.next() {
-                    match token {
-                        JsonToken::RightBracket => return Ok(JsonValue::Array(array)),
-                        _ => {
-                            self.position -= 1; // Move the position back for the next token to start at the array element
-                            let value = self.parse()?;
-                            array.push(value);
+        /// Mutable counterpart of `pointer`, for editing a nested value in place.
+        pub fn pointer_mut(&mut self, path: &str) -> Option<&mut JsonValue> {
+            if path.is_empty() {
+                return Some(self);
+            }
+            let mut current = self;
+            for segment in path.strip_prefix('/')?.split('/') {
+                let segment = unescape_pointer_segment(segment);
+                current = match current {
+                    JsonValue::Object(_) => current.get_mut(&segment)?,
+                    JsonValue::Array(_) => current.get_index_mut(parse_pointer_index(&segment)?)?,
+                    _ => return None,
+                };
+            }
+            Some(current)
+        }
     
-                            match self.next() {
-                                Some(JsonToken::Comma) => continue,
-                                Some(JsonToken::RightBracket) => return Ok(JsonValue::Array(array)),
-                                _ => return Err(ParseError::UnexpectedToken(token.clone())),
-                            }
-                        }
+        /// Looks up `key` in an object. With duplicate keys the first match wins.
+        pub fn get(&self, key: &str) -> Option<&JsonValue> {
+            match self {
+                JsonValue::Object(object) => object.iter().find(|(k, _)| k == key).map(|(_, value)| value),
+                _ => None,
+            }
+        }
+    
+        pub fn get_index(&self, i: usize) -> Option<&JsonValue> {
+            match self {
+                JsonValue::Array(array) => array.get(i),
+                _ => None,
+            }
+        }
+    
+        /// Mutable counterpart of `get`, again returning the first match for duplicate keys.
+        pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+            match self {
+                JsonValue::Object(object) => object.iter_mut().find(|(k, _)| k == key).map(|(_, value)| value),
+                _ => None,
+            }
+        }
+    
+        pub fn get_index_mut(&mut self, i: usize) -> Option<&mut JsonValue> {
+            match self {
+                JsonValue::Array(array) => array.get_mut(i),
+                _ => None,
+            }
+        }
+    
+        /// Takes the first entry with this key out of an object, keeping the others in order.
+        pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+            match self {
+                JsonValue::Object(object) => {
+                    let i = object.iter().position(|(k, _)| k == key)?;
+                    Some(object.remove(i).1)
+                },
+                _ => None,
+            }
+        }
+    }
+    
+    /// Callbacks for `JsonValue::visit`, which walks a tree depth-first in document order.
+    /// Every method does nothing by default, so implement just the ones you need.
+    pub trait Visitor {
+        fn visit_null(&mut self) {}
+        fn visit_bool(&mut self, _b: bool) {}
+        /// Any of `Number`, `Integer`, `UInteger` and `RawNumber`.
+        fn visit_number(&mut self, _number: &JsonValue) {}
+        fn visit_string(&mut self, _s: &str) {}
+        fn start_array(&mut self, _len: usize) {}
+        fn end_array(&mut self) {}
+        fn start_object(&mut self, _len: usize) {}
+        /// Called with each entry's key, right before its value is visited.
+        fn visit_key(&mut self, _key: &str) {}
+        fn end_object(&mut self) {}
+    }
+    
+    impl JsonValue {
+        pub fn visit<V: Visitor>(&self, visitor: &mut V) {
+            match self {
+                JsonValue::Null => visitor.visit_null(),
+                JsonValue::Bool(b) => visitor.visit_bool(*b),
+                JsonValue::Number(_) | JsonValue::Integer(_) | JsonValue::UInteger(_) | JsonValue::RawNumber(_) => {
+                    visitor.visit_number(self)
+                },
+                JsonValue::String(s) => visitor.visit_string(s),
+                JsonValue::Array(array) => {
+                    visitor.start_array(array.len());
+                    for element in array {
+                        element.visit(visitor);
                     }
-                } else {
-                    return Err(ParseError::UnexpectedEnd);
+                    visitor.end_array();
+                },
+                JsonValue::Object(object) => {
+                    visitor.start_object(object.len());
+                    for (key, value) in object {
+                        visitor.visit_key(key);
+                        value.visit(visitor);
+                    }
+                    visitor.end_object();
+                },
+            }
+        }
+    }
+    
+    /// Why `apply_patch` rejected a patch; `index` is the position of the failing operation.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum PatchError {
+        NotAnArray,
+        // an operation that isn't an object, or lacks a member it needs
+        InvalidOperation { index: usize },
+        UnsupportedOperation { index: usize, op: String },
+        PathNotFound { index: usize, path: String },
+    }
+    
+    impl fmt::Display for PatchError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                PatchError::NotAnArray => write!(f, "a patch has to be an array of operations"),
+                PatchError::InvalidOperation { index } => write!(f, "patch operation {} is malformed", index),
+                PatchError::UnsupportedOperation { index, op } => write!(f, "patch operation {} has unsupported op {:?}", index, op),
+                PatchError::PathNotFound { index, path } => write!(f, "patch operation {} refers to missing path {:?}", index, path),
+            }
+        }
+    }
+    
+    impl std::error::Error for PatchError {}
+    
+    impl JsonValue {
+        /// Applies a JSON Patch (RFC 6902) document. Only `add`, `remove` and `replace` are
+        /// supported so far. Either every operation applies or, on error, `self` is left as it was.
+        pub fn apply_patch(&mut self, patch: &JsonValue) -> Result<(), PatchError> {
+            let operations = patch.as_array().ok_or(PatchError::NotAnArray)?;
+            let mut patched = self.clone();
+            for (index, operation) in operations.iter().enumerate() {
+                let op = operation.get("op").and_then(JsonValue::as_str).ok_or(PatchError::InvalidOperation { index })?;
+                let path = operation.get("path").and_then(JsonValue::as_str).ok_or(PatchError::InvalidOperation { index })?;
+                let not_found = || PatchError::PathNotFound { index, path: path.to_string() };
+                match op {
+                    "add" | "replace" => {
+                        let value = operation.get("value").ok_or(PatchError::InvalidOperation { index })?.clone();
+                        if op == "add" {
+                            patched.patch_add(path, value).ok_or_else(not_found)?;
+                        } else {
+                            *patched.pointer_mut(path).ok_or_else(not_found)? = value;
+                        }
+                    },
+                    "remove" => {
+                        patched.patch_remove(path).ok_or_else(not_found)?;
+                    },
+                    _ => return Err(PatchError::UnsupportedOperation { index, op: op.to_string() }),
                 }
             }
+            *self = patched;
+            Ok(())
+        }
+    
+        fn patch_add(&mut self, path: &str, value: JsonValue) -> Option<()> {
+            let Some((parent, last)) = path.rsplit_once('/') else {
+                // only the empty path, the whole document, has no '/'
+                return path.is_empty().then(|| *self = value);
+            };
+            let last = unescape_pointer_segment(last);
+            match self.pointer_mut(parent)? {
+                JsonValue::Object(object) => {
+                    match object.iter_mut().find(|(k, _)| *k == last) {
+                        Some((_, existing)) => *existing = value,
+                        None => object.push((last, value)),
+                    }
+                },
+                JsonValue::Array(array) => {
+                    // "-" appends, any other index up to the length inserts before it
+                    let i = if last == "-" { array.len() } else { parse_pointer_index(&last)? };
+                    if i > array.len() {
+                        return None;
+                    }
+                    array.insert(i, value);
+                },
+                _ => return None,
+            }
+            Some(())
+        }
+    
+        fn patch_remove(&mut self, path: &str) -> Option<JsonValue> {
+            let (parent, last) = path.rsplit_once('/')?;
+            let last = unescape_pointer_segment(last);
+            match self.pointer_mut(parent)? {
+                JsonValue::Object(object) => {
+                    let i = object.iter().position(|(k, _)| *k == last)?;
+                    Some(object.remove(i).1)
+                },
+                JsonValue::Array(array) => {
+                    let i = parse_pointer_index(&last)?;
+                    (i < array.len()).then(|| array.remove(i))
+                },
+                _ => None,
+            }
         }
     }
-
-
-    use std::fs::read_to_string;
-    pub fn load_from_file(path: &str) -> JsonValue {
-        let file_data = read_to_string(path).unwrap();
-        let mut tokenizer = JsonTokenizer::new(&file_data);
-        let tokens = tokenizer.tokenize().unwrap();
-        let mut parser = JsonParser::new(&tokens);
-        parser.parse().unwrap()
+    
+    fn integer_value(value: &JsonValue) -> Option<i128> {
+        // wide enough to compare Integer and UInteger without loss
+        match value {
+            JsonValue::Integer(num) => Some(*num as i128),
+            JsonValue::UInteger(num) => Some(*num as i128),
+            JsonValue::RawNumber(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+    
+    fn unescape_pointer_segment(segment: &str) -> String {
+        // ~1 has to be decoded first so that "~01" becomes "~1" and not "/"
+        segment.replace("~1", "/").replace("~0", "~")
+    }
+    
+    fn parse_pointer_index(segment: &str) -> Option<usize> {
+        // array indices are plain decimal, without sign or leading zeros
+        if segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_digit()) || (segment.len() > 1 && segment.starts_with('0')) {
+            return None;
+        }
+        segment.parse().ok()
+    }
+    
+    /// `Null`, JSON's way of saying there's no value.
+    impl Default for JsonValue {
+        fn default() -> Self {
+            JsonValue::Null
+        }
+    }
+    
+    impl From<bool> for JsonValue {
+        fn from(b: bool) -> Self {
+            JsonValue::Bool(b)
+        }
+    }
+    
+    impl From<f64> for JsonValue {
+        fn from(num: f64) -> Self {
+            JsonValue::Number(num)
+        }
+    }
+    
+    impl From<i64> for JsonValue {
+        fn from(num: i64) -> Self {
+            JsonValue::Integer(num)
+        }
+    }
+    
+    impl From<u64> for JsonValue {
+        fn from(num: u64) -> Self {
+            // keep a single representation for values that fit in i64
+            match i64::try_from(num) {
+                Ok(num) => JsonValue::Integer(num),
+                Err(_) => JsonValue::UInteger(num),
+            }
+        }
+    }
+    
+    impl From<String> for JsonValue {
+        fn from(s: String) -> Self {
+            JsonValue::String(s)
+        }
+    }
+    
+    impl From<&str> for JsonValue {
+        fn from(s: &str) -> Self {
+            JsonValue::String(s.to_string())
+        }
+    }
+    
+    impl From<Vec<JsonValue>> for JsonValue {
+        fn from(array: Vec<JsonValue>) -> Self {
+            JsonValue::Array(array)
+        }
+    }
+    
+    impl From<Vec<(String, JsonValue)>> for JsonValue {
+        fn from(object: Vec<(String, JsonValue)>) -> Self {
+            JsonValue::Object(object)
+        }
+    }
+    
+    // serde_json's Map sorts its keys unless its own preserve_order feature is on, and keeps
+    // only the last of duplicate keys, so source order doesn't survive this direction
+    #[cfg(feature = "serde")]
+    impl From<JsonValue> for serde_json::Value {
+        fn from(value: JsonValue) -> Self {
+            match value {
+                JsonValue::Null => serde_json::Value::Null,
+                JsonValue::Bool(b) => serde_json::Value::Bool(b),
+                // serde_json has no NaN or infinity, they become null like in to_canonical_string
+                JsonValue::Number(num) => serde_json::Number::from_f64(num).map_or(serde_json::Value::Null, serde_json::Value::Number),
+                JsonValue::Integer(num) => serde_json::Value::Number(num.into()),
+                JsonValue::UInteger(num) => serde_json::Value::Number(num.into()),
+                JsonValue::RawNumber(text) => text.parse().map_or(serde_json::Value::Null, serde_json::Value::Number),
+                JsonValue::String(s) => serde_json::Value::String(s),
+                JsonValue::Array(array) => serde_json::Value::Array(array.into_iter().map(Into::into).collect()),
+                JsonValue::Object(object) => serde_json::Value::Object(object.into_iter().map(|(k, v)| (k, v.into())).collect()),
+            }
+        }
+    }
+    
+    #[cfg(feature = "serde")]
+    impl From<serde_json::Value> for JsonValue {
+        fn from(value: serde_json::Value) -> Self {
+            match value {
+                serde_json::Value::Null => JsonValue::Null,
+                serde_json::Value::Bool(b) => JsonValue::Bool(b),
+                serde_json::Value::Number(num) => match (num.as_i64(), num.as_u64()) {
+                    (Some(num), _) => JsonValue::Integer(num),
+                    (None, Some(num)) => JsonValue::UInteger(num),
+                    _ => JsonValue::Number(num.as_f64().unwrap_or(f64::NAN)),
+                },
+                serde_json::Value::String(s) => JsonValue::String(s),
+                serde_json::Value::Array(array) => JsonValue::Array(array.into_iter().map(Into::into).collect()),
+                serde_json::Value::Object(object) => JsonValue::Object(object.into_iter().map(|(k, v)| (k, v.into())).collect()),
+            }
+        }
+    }
+    
+    /// The kind of a `JsonValue`, see `JsonValue::value_type`. Every numeric variant is a
+    /// `Number`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum JsonType {
+        Null,
+        Bool,
+        Number,
+        String,
+        Array,
+        Object,
+    }
+    
+    impl fmt::Display for JsonType {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(match self {
+                JsonType::Null => "null",
+                JsonType::Bool => "boolean",
+                JsonType::Number => "number",
+                JsonType::String => "string",
+                JsonType::Array => "array",
+                JsonType::Object => "object",
+            })
+        }
+    }
+    
+    /// Returned by the `TryFrom<JsonValue>` conversions when the value holds another type.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TypeError {
+        pub expected: JsonType,
+        pub found: JsonType,
+        /// Set by the `i64` conversions for a number they can't hold, a fraction or an integer
+        /// past `i64::MAX`; `found` is then a `Number` too.
+        pub out_of_range: bool,
+    }
+    
+    impl fmt::Display for TypeError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            if self.out_of_range {
+                write!(f, "{} out of range for i64", self.found)
+            } else {
+                write!(f, "expected {}, found {}", self.expected, self.found)
+            }
+        }
+    }
+    
+    impl std::error::Error for TypeError {}
+    
+    impl JsonValue {
+        fn type_error(&self, expected: JsonType) -> TypeError {
+            let found = self.value_type();
+            TypeError { expected, found, out_of_range: false }
+        }
+    }
+    
+    impl TryFrom<&JsonValue> for bool {
+        type Error = TypeError;
+    
+        fn try_from(value: &JsonValue) -> Result<Self, TypeError> {
+            value.as_bool().ok_or_else(|| value.type_error(JsonType::Bool))
+        }
+    }
+    
+    impl TryFrom<&JsonValue> for f64 {
+        type Error = TypeError;
+    
+        fn try_from(value: &JsonValue) -> Result<Self, TypeError> {
+            value.as_f64().ok_or_else(|| value.type_error(JsonType::Number))
+        }
+    }
+    
+    /// Floats convert only when they hold an exact integer, as with `as_i64`.
+    impl TryFrom<&JsonValue> for i64 {
+        type Error = TypeError;
+    
+        fn try_from(value: &JsonValue) -> Result<Self, TypeError> {
+            value.as_i64().ok_or_else(|| {
+                let error = value.type_error(JsonType::Number);
+                TypeError { out_of_range: error.found == JsonType::Number, ..error }
+            })
+        }
+    }
+    
+    impl TryFrom<&JsonValue> for String {
+        type Error = TypeError;
+    
+        fn try_from(value: &JsonValue) -> Result<Self, TypeError> {
+            value.as_str().map(str::to_string).ok_or_else(|| value.type_error(JsonType::String))
+        }
+    }
+    
+    impl TryFrom<&JsonValue> for Vec<JsonValue> {
+        type Error = TypeError;
+    
+        fn try_from(value: &JsonValue) -> Result<Self, TypeError> {
+            value.as_array().map(<[JsonValue]>::to_vec).ok_or_else(|| value.type_error(JsonType::Array))
+        }
+    }
+    
+    impl TryFrom<JsonValue> for bool {
+        type Error = TypeError;
+    
+        fn try_from(value: JsonValue) -> Result<Self, TypeError> {
+            bool::try_from(&value)
+        }
+    }
+    
+    impl TryFrom<JsonValue> for f64 {
+        type Error = TypeError;
+    
+        fn try_from(value: JsonValue) -> Result<Self, TypeError> {
+            f64::try_from(&value)
+        }
+    }
+    
+    impl TryFrom<JsonValue> for i64 {
+        type Error = TypeError;
+    
+        fn try_from(value: JsonValue) -> Result<Self, TypeError> {
+            i64::try_from(&value)
+        }
+    }
+    
+    impl TryFrom<JsonValue> for String {
+        type Error = TypeError;
+    
+        fn try_from(value: JsonValue) -> Result<Self, TypeError> {
+            match value {
+                JsonValue::String(s) => Ok(s),
+                other => Err(other.type_error(JsonType::String)),
+            }
+        }
+    }
+    
+    impl TryFrom<JsonValue> for Vec<JsonValue> {
+        type Error = TypeError;
+    
+        fn try_from(value: JsonValue) -> Result<Self, TypeError> {
+            match value {
+                JsonValue::Array(array) => Ok(array),
+                other => Err(other.type_error(JsonType::Array)),
+            }
+        }
+    }
+    
+    static NULL: JsonValue = JsonValue::Null;
+    
+    /// `value["key"]` returns `Null` instead of panicking when the key is missing
+    /// or `value` isn't an object, so lookups can be chained.
+    impl std::ops::Index<&str> for JsonValue {
+        type Output = JsonValue;
+    
+        fn index(&self, key: &str) -> &JsonValue {
+            self.get(key).unwrap_or(&NULL)
+        }
+    }
+    
+    /// `value[i]` returns `Null` instead of panicking when `i` is out of bounds
+    /// or `value` isn't an array.
+    impl std::ops::Index<usize> for JsonValue {
+        type Output = JsonValue;
+    
+        fn index(&self, i: usize) -> &JsonValue {
+            self.get_index(i).unwrap_or(&NULL)
+        }
+    }
+    
+    fn write_string<W: fmt::Write>(out: &mut W, s: &str) -> fmt::Result {
+        out.write_char('"')?;
+        for ch in s.chars() {
+            match ch {
+                '"' => out.write_str("\\\"")?,
+                '\\' => out.write_str("\\\\")?,
+                '\n' => out.write_str("\\n")?,
+                '\r' => out.write_str("\\r")?,
+                '\t' => out.write_str("\\t")?,
+                '\u{8}' => out.write_str("\\b")?,
+                '\u{c}' => out.write_str("\\f")?,
+                // raw control characters aren't allowed inside JSON strings
+                '\u{0}'..='\u{1f}' => write!(out, "\\u{:04x}", ch as u32)?,
+                _ => out.write_char(ch)?,
+            }
+        }
+        out.write_char('"')
+    }
+    
+    fn write_number<W: fmt::Write>(out: &mut W, num: f64) -> fmt::Result {
+        if num.is_nan() {
+            // not valid JSON, but what the allow_nan_infinity extension reads back
+            out.write_str("NaN")
+        } else if num.is_infinite() {
+            out.write_str(if num > 0.0 { "Infinity" } else { "-Infinity" })
+        } else {
+            write_number_ecmascript(out, num)
+        }
+    }
+    
+    fn write_value<W: fmt::Write>(out: &mut W, value: &JsonValue) -> fmt::Result {
+        match value {
+            JsonValue::Null => out.write_str("null"),
+            JsonValue::Bool(b) => write!(out, "{}", b),
+            JsonValue::Number(num) => write_number(out, *num),
+            JsonValue::Integer(num) => write!(out, "{}", num),
+            JsonValue::UInteger(num) => write!(out, "{}", num),
+            JsonValue::RawNumber(text) => out.write_str(text),
+            JsonValue::String(s) => write_string(out, s),
+            JsonValue::Array(array) => {
+                out.write_char('[')?;
+                for (i, element) in array.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    write_value(out, element)?;
+                }
+                out.write_char(']')
+            },
+            JsonValue::Object(object) => {
+                out.write_char('{')?;
+                for (i, (key, element)) in object.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    write_string(out, key)?;
+                    out.write_char(':')?;
+                    write_value(out, element)?;
+                }
+                out.write_char('}')
+            },
+        }
+    }
+    
+    impl fmt::Display for JsonValue {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_value(f, self)
+        }
+    }
+    
+    fn write_indent<W: fmt::Write>(out: &mut W, width: usize) -> fmt::Result {
+        out.write_char('\n')?;
+        for _ in 0..width {
+            out.write_char(' ')?;
+        }
+        Ok(())
+    }
+    
+    fn write_value_pretty<W: fmt::Write>(out: &mut W, value: &JsonValue, indent: usize, depth: usize) -> fmt::Result {
+        match value {
+            JsonValue::Array(array) if !array.is_empty() => {
+                out.write_char('[')?;
+                for (i, element) in array.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    write_indent(out, indent * (depth + 1))?;
+                    write_value_pretty(out, element, indent, depth + 1)?;
+                }
+                write_indent(out, indent * depth)?;
+                out.write_char(']')
+            },
+            JsonValue::Object(object) if !object.is_empty() => {
+                out.write_char('{')?;
+                for (i, (key, element)) in object.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    write_indent(out, indent * (depth + 1))?;
+                    write_string(out, key)?;
+                    out.write_str(": ")?;
+                    write_value_pretty(out, element, indent, depth + 1)?;
+                }
+                write_indent(out, indent * depth)?;
+                out.write_char('}')
+            },
+            // scalars and empty containers look the same as in compact output
+            _ => write_value(out, value),
+        }
+    }
+    
+    pub fn to_string_pretty(value: &JsonValue, indent: usize) -> String {
+        let mut out = String::new();
+        write_value_pretty(&mut out, value, indent, 0).unwrap();
+        out
+    }
+    
+    // `canonical` switches to RFC 8785 rules: keys ordered by UTF-16 code units and numbers
+    // written the way ECMAScript does
+    fn write_value_sorted<W: fmt::Write>(out: &mut W, value: &JsonValue, canonical: bool) -> fmt::Result {
+        match value {
+            JsonValue::Array(array) => {
+                out.write_char('[')?;
+                for (i, element) in array.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    write_value_sorted(out, element, canonical)?;
+                }
+                out.write_char(']')
+            },
+            JsonValue::Object(object) => {
+                // stable, so repeated keys keep their relative order
+                let mut entries: Vec<_> = object.iter().collect();
+                if canonical {
+                    entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                } else {
+                    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
+                out.write_char('{')?;
+                for (i, (key, element)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(',')?;
+                    }
+                    write_string(out, key)?;
+                    out.write_char(':')?;
+                    write_value_sorted(out, element, canonical)?;
+                }
+                out.write_char('}')
+            },
+            _ if canonical && value.is_number() => write_number_canonical(out, value.as_f64().unwrap_or(f64::NAN)),
+            _ => write_value(out, value),
+        }
+    }
+    
+    /// Compact output with the keys of every object, nested ones too, in byte order, so
+    /// equal documents serialize identically whatever order their keys came in.
+    pub fn to_string_sorted(value: &JsonValue) -> String {
+        let mut out = String::new();
+        write_value_sorted(&mut out, value, false).unwrap();
+        out
+    }
+    
+    fn write_number_canonical<W: fmt::Write>(out: &mut W, num: f64) -> fmt::Result {
+        if !num.is_finite() {
+            // RFC 8785 has no form for these; JSON.stringify writes null too
+            return out.write_str("null");
+        }
+        write_number_ecmascript(out, num)
+    }
+    
+    fn write_number_ecmascript<W: fmt::Write>(out: &mut W, num: f64) -> fmt::Result {
+        // ECMAScript's Number::toString for a finite `num`, which RFC 8785 adopts for every
+        // number: the shortest digits that read back as the same f64, with an exponent only
+        // below 1e-6 or from 1e21 on
+        if num == 0.0 {
+            return out.write_char('0');
+        }
+        if num < 0.0 {
+            out.write_char('-')?;
+        }
+        // `{:e}` gives the shortest digits that round-trip, as in "1.2345e-7"
+        let scientific = format!("{:e}", num.abs());
+        let (mantissa, exponent) = scientific.split_once('e').unwrap();
+        let mut digits = mantissa.replace('.', "");
+        let k = digits.len() as i32;
+        // when the value lies exactly halfway between two shortest candidates, Rust takes the
+        // upper one but ECMAScript wants the even one
+        let last = digits.as_bytes()[digits.len() - 1];
+        if k > 1 && last % 2 == 1 {
+            let mut lower = digits.clone();
+            lower.pop();
+            lower.push((last - 1) as char);
+            let lower_value = format!("{}.{}e{}", &lower[..1], &lower[1..], exponent).parse::<f64>();
+            // this is on the path of every serialized float, so the cheap check goes first
+            // and the full expansion is only written out for the rare candidates
+            if lower_value == Ok(num.abs()) {
+                let exact = format!("{:.1100e}", num.abs());
+                let exact_digits = exact.split_once('e').unwrap().0.replace('.', "");
+                if exact_digits.trim_end_matches('0') == format!("{}5", lower) {
+                    digits = lower;
+                }
+            }
+        }
+        // the decimal point goes after the first `n` digits
+        let n = exponent.parse::<i32>().unwrap() + 1;
+        if k <= n && n <= 21 {
+            out.write_str(&digits)?;
+            for _ in 0..n - k {
+                out.write_char('0')?;
+            }
+            Ok(())
+        } else if 0 < n && n <= 21 {
+            write!(out, "{}.{}", &digits[..n as usize], &digits[n as usize..])
+        } else if -6 < n && n <= 0 {
+            out.write_str("0.")?;
+            for _ in 0..-n {
+                out.write_char('0')?;
+            }
+            out.write_str(&digits)
+        } else {
+            let sign = if n > 0 { '+' } else { '-' };
+            if k == 1 {
+                write!(out, "{}e{}{}", digits, sign, (n - 1).abs())
+            } else {
+                write!(out, "{}.{}e{}{}", &digits[..1], &digits[1..], sign, (n - 1).abs())
+            }
+        }
+    }
+    
+    /// RFC 8785 (JCS) canonical form, for hashing or signing: no whitespace, keys sorted by
+    /// their UTF-16 code units, and every number written as the shortest ECMAScript form of
+    /// its `f64` value, so integers past 2^53 lose precision. NaN and infinities, which the
+    /// RFC doesn't allow, are written as `null`.
+    pub fn to_canonical_string(value: &JsonValue) -> String {
+        let mut out = String::new();
+        write_value_sorted(&mut out, value, true).unwrap();
+        out
+    }
+    
+    /// Output settings for `to_string_with_options`. The defaults give the same compact text
+    /// as `to_string`.
+    #[derive(Debug, Clone, Default)]
+    pub struct SerializeOptions {
+        /// Pretty-print with this many spaces per level, like `to_string_pretty`.
+        pub indent: Option<usize>,
+        /// Write `/` as `\/`, which some consumers expect.
+        pub escape_slashes: bool,
+        /// Write `<`, `>` and `&` as `\u003c`, `\u003e` and `\u0026`, so the output can be
+        /// inlined into an HTML `<script>` element without a string closing the tag.
+        pub escape_html: bool,
+    }
+    
+    impl SerializeOptions {
+        pub fn new() -> Self {
+            SerializeOptions::default()
+        }
+    
+        pub fn indent(mut self, indent: usize) -> Self {
+            self.indent = Some(indent);
+            self
+        }
+    
+        pub fn escape_slashes(mut self, escape: bool) -> Self {
+            self.escape_slashes = escape;
+            self
+        }
+    
+        pub fn escape_html(mut self, escape: bool) -> Self {
+            self.escape_html = escape;
+            self
+        }
+    }
+    
+    // none of the escaped characters can appear outside a string in serialized output, so
+    // every occurrence passing through is rewritten without tracking where strings are
+    struct EscapingWriter<'w, W: fmt::Write> {
+        inner: &'w mut W,
+        options: &'w SerializeOptions,
+    }
+    
+    impl<W: fmt::Write> fmt::Write for EscapingWriter<'_, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let mut written = 0;
+            for (i, ch) in s.char_indices() {
+                let escaped = match ch {
+                    '/' if self.options.escape_slashes => "\\/",
+                    '<' if self.options.escape_html => "\\u003c",
+                    '>' if self.options.escape_html => "\\u003e",
+                    '&' if self.options.escape_html => "\\u0026",
+                    _ => continue,
+                };
+                self.inner.write_str(&s[written..i])?;
+                self.inner.write_str(escaped)?;
+                written = i + 1;
+            }
+            self.inner.write_str(&s[written..])
+        }
+    }
+    
+    pub fn to_string_with_options(value: &JsonValue, options: &SerializeOptions) -> String {
+        let mut out = String::new();
+        let mut writer = EscapingWriter { inner: &mut out, options };
+        match options.indent {
+            Some(indent) => write_value_pretty(&mut writer, value, indent, 0),
+            None => write_value(&mut writer, value),
+        }
+        .unwrap();
+        out
+    }
+    
+    // lets the fmt::Write based serializer stream into an io::Write, keeping the io error around
+    struct IoWriter<'w, W: std::io::Write> {
+        inner: &'w mut W,
+        error: Option<std::io::Error>,
+    }
+    
+    impl<W: std::io::Write> fmt::Write for IoWriter<'_, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.inner.write_all(s.as_bytes()).map_err(|err| {
+                self.error = Some(err);
+                fmt::Error
+            })
+        }
+    }
+    
+    /// Writes are issued piece by piece, so wrap unbuffered sinks like files in a `BufWriter`.
+    pub fn to_writer<W: std::io::Write>(writer: &mut W, value: &JsonValue) -> std::io::Result<()> {
+        let mut adapter = IoWriter { inner: writer, error: None };
+        write_value(&mut adapter, value).map_err(|_| {
+            adapter.error.take().unwrap_or_else(|| std::io::Error::other("failed to serialize value"))
+        })
+    }
+    
+    /// Compact UTF-8 bytes, the same text `to_string` would produce.
+    pub fn to_vec(value: &JsonValue) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // writing into a Vec can't fail
+        to_writer(&mut bytes, value).expect("writing to a Vec failed");
+        bytes
+    }
+    
+    /// Where a parse error happened in the source; `span` is empty for the end of input.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Location {
+        pub span: Range<usize>,
+        pub line: usize,
+        pub column: usize,
+    }
+    
+    impl Location {
+        fn new(input: &str, span: Range<usize>) -> Self {
+            let (line, column) = line_column(input, span.start);
+            Location { span, line, column }
+        }
+    }
+    
+    // `location` is only known when the parser was given the source
+    #[derive(Debug)]
+    pub enum ParseError {
+        UnexpectedToken { token: JsonToken, location: Option<Location> },
+        UnexpectedEnd { location: Option<Location> },
+        // the input held nothing but whitespace (or comments)
+        EmptyInput,
+        InputTooLarge { len: usize, max: usize },
+        TrailingTokens { token: JsonToken, location: Option<Location> },
+        DepthLimitExceeded { location: Option<Location> },
+        DuplicateKey { key: String, location: Option<Location> },
+        // a scalar at the root while require_top_level_container is set
+        ExpectedContainer { location: Option<Location> },
+        // `token` turned up where punctuation was required
+        ExpectedColon { token: JsonToken, location: Option<Location> },
+        ExpectedCommaOrRightBrace { token: JsonToken, location: Option<Location> },
+        ExpectedCommaOrRightBracket { token: JsonToken, location: Option<Location> },
+    }
+    
+    fn write_location(f: &mut fmt::Formatter, location: &Option<Location>) -> fmt::Result {
+        match location {
+            Some(location) => write!(f, " at line {}, column {}", location.line, location.column),
+            None => Ok(()),
+        }
+    }
+    
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ParseError::UnexpectedToken { token, location } => {
+                    write!(f, "unexpected token '{}'", token)?;
+                    write_location(f, location)
+                },
+                ParseError::UnexpectedEnd { location } => {
+                    write!(f, "unexpected end of input")?;
+                    write_location(f, location)
+                },
+                ParseError::EmptyInput => write!(f, "empty input, expected a JSON value"),
+                ParseError::InputTooLarge { len, max } => write!(f, "input of {} bytes exceeds the limit of {} bytes", len, max),
+                ParseError::TrailingTokens { token, location } => {
+                    write!(f, "unexpected token '{}' after the top-level value", token)?;
+                    write_location(f, location)
+                },
+                ParseError::DepthLimitExceeded { location } => {
+                    write!(f, "nesting depth limit exceeded")?;
+                    write_location(f, location)
+                },
+                ParseError::DuplicateKey { key, location } => {
+                    write!(f, "duplicate key {:?}", key)?;
+                    write_location(f, location)
+                },
+                ParseError::ExpectedContainer { location } => {
+                    write!(f, "expected an object or array at the top level")?;
+                    write_location(f, location)
+                },
+                ParseError::ExpectedColon { token, location } => {
+                    write!(f, "expected ':' after object key, found '{}'", token)?;
+                    write_location(f, location)
+                },
+                ParseError::ExpectedCommaOrRightBrace { token, location } => {
+                    write!(f, "expected ',' or '}}' after object value, found '{}'", token)?;
+                    write_location(f, location)
+                },
+                ParseError::ExpectedCommaOrRightBracket { token, location } => {
+                    write!(f, "expected ',' or ']' after array element, found '{}'", token)?;
+                    write_location(f, location)
+                },
+            }
+        }
+    }
+    
+    impl std::error::Error for ParseError {}
+    
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+    
+    /// The defaults follow RFC 8259 strictly.
+    #[derive(Debug, Clone)]
+    pub struct ParserOptions {
+        pub max_depth: usize,
+        /// Fail with `DuplicateKey` when an object repeats a key, instead of keeping every entry.
+        pub reject_duplicate_keys: bool,
+        /// Accept a comma right before a closing `]` or `}`, as in `[1,2,]`.
+        pub allow_trailing_commas: bool,
+        /// Skip `// line` and `/* block */` comments between tokens.
+        pub allow_comments: bool,
+        /// Read `NaN`, `Infinity` and `-Infinity` as numbers. The serializer always writes
+        /// non-finite numbers this way, so such output only parses back with this enabled.
+        pub allow_nan_infinity: bool,
+        /// Keep each number's source text as a `RawNumber`, so `1.0` or `1.200` serialize back
+        /// exactly as written instead of going through `f64`. On by default with the
+        /// `arbitrary_precision` feature.
+        pub preserve_number_text: bool,
+        /// Accept raw control characters such as tabs or newlines inside strings, which
+        /// RFC 8259 requires to be escaped.
+        pub allow_control_characters: bool,
+        /// Read `'...'` string literals like `"..."` ones, and accept `\'` as an escape.
+        pub allow_single_quotes: bool,
+        /// Fail with `InputTooLarge` before tokenizing anything longer than this many bytes.
+        /// It's a coarse guard against oversized requests: it checks an input that is already
+        /// in memory, and doesn't bound what parsing it allocates.
+        pub max_input_len: Option<usize>,
+        /// Fail with `ExpectedContainer` unless the root is an object or array, for documents
+        /// such as config files where a bare `42` or `"x"` is certainly a mistake.
+        pub require_top_level_container: bool,
+    }
+    
+    impl Default for ParserOptions {
+        fn default() -> Self {
+            ParserOptions {
+                max_depth: DEFAULT_MAX_DEPTH,
+                reject_duplicate_keys: false,
+                allow_trailing_commas: false,
+                allow_comments: false,
+                allow_nan_infinity: false,
+                preserve_number_text: cfg!(feature = "arbitrary_precision"),
+                allow_control_characters: false,
+                allow_single_quotes: false,
+                max_input_len: None,
+                require_top_level_container: false,
+            }
+        }
+    }
+    
+    impl ParserOptions {
+        /// Strict options; chain the setters below to relax them, e.g.
+        /// `ParserOptions::new().allow_comments(true).max_depth(64)`.
+        pub fn new() -> Self {
+            ParserOptions::default()
+        }
+    
+        pub fn max_depth(mut self, max_depth: usize) -> Self {
+            self.max_depth = max_depth;
+            self
+        }
+    
+        pub fn reject_duplicate_keys(mut self, reject: bool) -> Self {
+            self.reject_duplicate_keys = reject;
+            self
+        }
+    
+        pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+            self.allow_trailing_commas = allow;
+            self
+        }
+    
+        pub fn allow_comments(mut self, allow: bool) -> Self {
+            self.allow_comments = allow;
+            self
+        }
+    
+        pub fn allow_nan_infinity(mut self, allow: bool) -> Self {
+            self.allow_nan_infinity = allow;
+            self
+        }
+    
+        pub fn preserve_number_text(mut self, preserve: bool) -> Self {
+            self.preserve_number_text = preserve;
+            self
+        }
+    
+        pub fn allow_control_characters(mut self, allow: bool) -> Self {
+            self.allow_control_characters = allow;
+            self
+        }
+    
+        pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+            self.allow_single_quotes = allow;
+            self
+        }
+    
+        pub fn max_input_len(mut self, max: usize) -> Self {
+            self.max_input_len = Some(max);
+            self
+        }
+    
+        pub fn require_top_level_container(mut self, require: bool) -> Self {
+            self.require_top_level_container = require;
+            self
+        }
+    }
+    
+    // an object or array whose contents are still being parsed; `seen` holds the object's
+    // keys so far when duplicates are rejected, pointing into the tokens or the source
+    enum Frame<'k, V, K> {
+        Array { array: Vec<V> },
+        Object { object: Vec<(K, V)>, key: K, seen: HashSet<&'k str> },
+    }
+    
+    // what's left after reading the start of a value or of a container's next element
+    enum Step<'k, V, K> {
+        Done(V),
+        Open(Frame<'k, V, K>),
+    }
+    
+    // the trees JsonParser can build: JsonValue copies every string out of its token, while
+    // BorrowedValue points into the source text wherever there was nothing to unescape
+    trait Build<'s>: Sized {
+        type Str: AsRef<str>;
+        // `raw` is the string's text in the source, when it's known and has no escapes
+        fn string(token: &str, raw: Option<&'s str>) -> Self::Str;
+        fn scalar(value: JsonValue) -> Self;
+        fn from_string(s: Self::Str) -> Self;
+        fn array(array: Vec<Self>) -> Self;
+        fn object(object: Vec<(Self::Str, Self)>) -> Self;
+    }
+    
+    impl Build<'_> for JsonValue {
+        type Str = String;
+        fn string(token: &str, _raw: Option<&str>) -> String {
+            token.to_string()
+        }
+        fn scalar(value: JsonValue) -> Self {
+            value
+        }
+        fn from_string(s: String) -> Self {
+            JsonValue::String(s)
+        }
+        fn array(array: Vec<Self>) -> Self {
+            JsonValue::Array(array)
+        }
+        fn object(object: Vec<(String, Self)>) -> Self {
+            JsonValue::Object(object)
+        }
+    }
+    
+    // beyond this a wrong guess wastes more than the reallocations it saves
+    const MAX_RESERVED_ELEMENTS: usize = 256;
+    
+    fn remember_size(sizes: &mut Vec<usize>, depth: usize, len: usize) {
+        if sizes.len() <= depth {
+            sizes.resize(depth + 1, 0);
+        }
+        sizes[depth] = len.min(MAX_RESERVED_ELEMENTS);
+    }
+    
+    pub struct JsonParser<'a> {
+        tokens: &'a [JsonToken],
+        // the input and the byte range of every token in it, when known
+        source: Option<(&'a str, &'a [Range<usize>])>,
+        position: usize,
+        options: ParserOptions,
+    }
+    
+    impl<'a> JsonParser<'a> {
+        pub fn new(tokens: &'a [JsonToken]) -> Self {
+            JsonParser::with_options(tokens, ParserOptions::default())
+        }
+    
+        pub fn with_options(tokens: &'a [JsonToken], options: ParserOptions) -> Self {
+            JsonParser { tokens, source: None, position: 0, options }
+        }
+    
+        /// The text the tokens came from and each token's byte range in it, so that errors
+        /// can carry a location.
+        pub fn source(mut self, input: &'a str, spans: &'a [Range<usize>]) -> Self {
+            self.source = Some((input, spans));
+            self
+        }
+    
+        fn next(&mut self) -> Option<&'a JsonToken> {
+            let token = self.tokens.get(self.position);
+            self.position += 1;
+            token
+        }
+    
+        fn location(&self, index: usize) -> Option<Location> {
+            let (input, spans) = self.source?;
+            Some(Location::new(input, spans.get(index)?.clone()))
+        }
+    
+        fn token(&self, index: usize) -> JsonToken {
+            // a string left empty by the tokenizer's skip_plain_strings is recovered from the source
+            match &self.tokens[index] {
+                JsonToken::String(_) => match self.raw_string(self.source.map(|(input, _)| input), index) {
+                    Some(raw) => JsonToken::String(raw.to_string()),
+                    None => self.tokens[index].clone(),
+                },
+                token => token.clone(),
+            }
+        }
+    
+        fn unexpected(&self, index: usize) -> ParseError {
+            ParseError::UnexpectedToken { token: self.token(index), location: self.location(index) }
+        }
+    
+        fn unexpected_end(&self) -> ParseError {
+            let location = self.source.map(|(input, _)| Location::new(input, input.len()..input.len()));
+            ParseError::UnexpectedEnd { location }
+        }
+    
+        // the last `next()` should have returned some punctuation: `error` reports the token
+        // it returned instead, or the end of input if it ran out
+        fn expected(&self, error: impl FnOnce(JsonToken, Option<Location>) -> ParseError) -> ParseError {
+            if self.position <= self.tokens.len() {
+                error(self.token(self.position - 1), self.location(self.position - 1))
+            } else {
+                self.unexpected_end()
+            }
+        }
+    
+        pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+            self.build(None)
+        }
+    
+        // the source text of string token `index` without its quotes, unless it has escapes
+        fn raw_string<'s>(&self, input: Option<&'s str>, index: usize) -> Option<&'s str> {
+            let span = self.source?.1.get(index)?;
+            let raw = input?.get(span.start + 1..span.end - 1)?;
+            if raw.contains('\\') { None } else { Some(raw) }
+        }
+    
+        fn build<'s, V: Build<'s>>(&mut self, input: Option<&'s str>) -> Result<V, ParseError> {
+            // open containers live on this stack instead of the call stack, so nesting
+            // is only bounded by max_depth and memory
+            let mut stack: Vec<Frame<V, V::Str>> = Vec::new();
+            // the length of the container last closed at each depth; siblings in something like
+            // an array of records mostly share a shape, so the next one reserves that up front
+            let mut sizes: Vec<usize> = Vec::new();
+            let start = self.position;
+            if self.position >= self.tokens.len() {
+                return Err(ParseError::EmptyInput);
+            }
+            let container = matches!(self.tokens[self.position], JsonToken::LeftBrace | JsonToken::LeftBracket);
+            if self.options.require_top_level_container && !container {
+                return Err(ParseError::ExpectedContainer { location: self.location(self.position) });
+            }
+            loop {
+                let token = self.next().ok_or_else(|| self.unexpected_end())?;
+                let mut step = match token {
+                    JsonToken::Null => Step::Done(V::scalar(JsonValue::Null)),
+                    JsonToken::True => Step::Done(V::scalar(JsonValue::Bool(true))),
+                    JsonToken::False => Step::Done(V::scalar(JsonValue::Bool(false))),
+                    JsonToken::Number(num) => Step::Done(V::scalar(JsonValue::Number(*num))),
+                    JsonToken::Integer(num) => Step::Done(V::scalar(JsonValue::Integer(*num))),
+                    JsonToken::UInteger(num) => Step::Done(V::scalar(JsonValue::UInteger(*num))),
+                    JsonToken::RawNumber(text) => Step::Done(V::scalar(JsonValue::RawNumber(text.clone()))),
+                    JsonToken::String(s) => {
+                        let raw = self.raw_string(input, self.position - 1);
+                        Step::Done(V::from_string(V::string(s, raw)))
+                    },
+                    JsonToken::LeftBrace | JsonToken::LeftBracket => {
+                        if stack.len() >= self.options.max_depth {
+                            return Err(ParseError::DepthLimitExceeded { location: self.location(self.position - 1) });
+                        }
+                        if *token == JsonToken::LeftBrace {
+                            self.start_entry(Vec::new(), HashSet::new(), input)?
+                        } else {
+                            self.start_element(Vec::new())?
+                        }
+                    },
+                    _ => return Err(self.unexpected(self.position - 1)),
+                };
+    
+                // hand finished values up to their containers until one of them needs another value
+                loop {
+                    let value = match step {
+                        Step::Open(frame) => {
+                            stack.push(frame);
+                            break;
+                        },
+                        Step::Done(value) => value,
+                    };
+                    step = match stack.pop() {
+                        None => return Ok(value),
+                        Some(Frame::Array { mut array }) => {
+                            if array.is_empty() {
+                                array.reserve(self.reserved_size(&sizes, stack.len(), start));
+                            }
+                            array.push(value);
+                            match self.next() {
+                                Some(JsonToken::Comma) => self.start_element(array)?,
+                                Some(JsonToken::RightBracket) => {
+                                    remember_size(&mut sizes, stack.len(), array.len());
+                                    Step::Done(V::array(array))
+                                },
+                                _ => return Err(self.expected(|token, location| ParseError::ExpectedCommaOrRightBracket { token, location })),
+                            }
+                        },
+                        Some(Frame::Object { mut object, key, seen }) => {
+                            if object.is_empty() {
+                                object.reserve(self.reserved_size(&sizes, stack.len(), start));
+                            }
+                            object.push((key, value));
+                            match self.next() {
+                                Some(JsonToken::Comma) => self.start_entry(object, seen, input)?,
+                                Some(JsonToken::RightBrace) => {
+                                    remember_size(&mut sizes, stack.len(), object.len());
+                                    Step::Done(V::object(object))
+                                },
+                                _ => return Err(self.expected(|token, location| ParseError::ExpectedCommaOrRightBrace { token, location })),
+                            }
+                        },
+                    };
+                }
+            }
+        }
+    
+        // called once the first element of a container is done
+        fn reserved_size(&self, sizes: &[usize], depth: usize, start: usize) -> usize {
+            if depth > 0 {
+                return sizes.get(depth).copied().unwrap_or(0);
+            }
+            // the root has no siblings to go by, so guess that every element takes as many tokens
+            // as the first one did; that's exact for a flat array of numbers, and never reserves
+            // more entries than there are tokens left
+            let first = self.position - start - 1;
+            (self.tokens.len() - self.position) / (first + 1) + 1
+        }
+    
+        fn start_entry<'s, 'k, V: Build<'s>>(
+            &mut self,
+            object: Vec<(V::Str, V)>,
+            mut seen: HashSet<&'k str>,
+            input: Option<&'s str>,
+        ) -> Result<Step<'k, V, V::Str>, ParseError>
+        where
+            'a: 'k,
+            's: 'k,
+        {
+            // called after '{' or a comma; a non-empty object means we're past a comma
+            let token = self.next().ok_or_else(|| self.unexpected_end())?;
+            let index = self.position - 1;
+            match token {
+                JsonToken::RightBrace if !object.is_empty() && !self.options.allow_trailing_commas => {
+                    Err(self.unexpected(index))
+                },
+                JsonToken::RightBrace => Ok(Step::Done(V::object(object))),
+                JsonToken::String(key) => {
+                    if let Some(JsonToken::Colon) = self.next() {
+                        let raw = self.raw_string(input, index);
+                        // a set instead of scanning `object`, which a payload of many keys
+                        // would turn quadratic
+                        if self.options.reject_duplicate_keys && !seen.insert(raw.unwrap_or(key)) {
+                            return Err(ParseError::DuplicateKey { key: raw.unwrap_or(key).to_string(), location: self.location(index) });
+                        }
+                        let key = V::string(key, raw);
+                        Ok(Step::Open(Frame::Object { object, key, seen }))
+                    } else {
+                        Err(self.expected(|token, location| ParseError::ExpectedColon { token, location }))
+                    }
+                },
+                _ => Err(self.unexpected(index)),
+            }
+        }
+    
+        fn start_element<'s, 'k, V: Build<'s>>(&mut self, array: Vec<V>) -> Result<Step<'k, V, V::Str>, ParseError> {
+            // called after '[' or a comma; a non-empty array means we're past a comma
+            let token = self.tokens.get(self.position).ok_or_else(|| self.unexpected_end())?;
+            match token {
+                JsonToken::RightBracket if !array.is_empty() && !self.options.allow_trailing_commas => {
+                    Err(self.unexpected(self.position))
+                },
+                JsonToken::RightBracket => {
+                    self.position += 1;
+                    Ok(Step::Done(V::array(array)))
+                },
+                _ => Ok(Step::Open(Frame::Array { array })),
+            }
+        }
+    }
+
+    /// The one error type of the `parse_*`, `from_reader` and `load_from_file` entry points.
+    /// `tokenize` and `JsonParser` report the `TokenizeError` and `ParseError` wrapped here.
+    #[derive(Debug)]
+    pub enum JsonError {
+        /// Reading the input failed.
+        Io(std::io::Error),
+        /// The input bytes weren't valid UTF-8.
+        Utf8(std::str::Utf8Error),
+        /// The input couldn't be split into tokens, e.g. an unclosed string or a bad escape.
+        Tokenize(TokenizeError),
+        /// The tokens don't form a valid document, or broke a `ParserOptions` limit.
+        Parse(ParseError),
+    }
+    
+    impl JsonError {
+        /// The 1-based line and column the error points at, for errors that have one.
+        pub fn line_column(&self) -> Option<(usize, usize)> {
+            match self {
+                JsonError::Tokenize(err) => Some(match *err {
+                    TokenizeError::UnexpectedCharacter { line, column, .. }
+                    | TokenizeError::InvalidEscape { line, column, .. }
+                    | TokenizeError::InvalidUnicodeEscape { line, column }
+                    | TokenizeError::UnpairedSurrogate { line, column }
+                    | TokenizeError::UnclosedString { line, column, .. }
+                    | TokenizeError::MalformedNumber { line, column }
+                    | TokenizeError::LeadingZero { line, column }
+                    | TokenizeError::UnclosedComment { line, column }
+                    | TokenizeError::NumberOutOfRange { line, column }
+                    | TokenizeError::ControlCharacter { line, column, .. }
+                    | TokenizeError::UnexpectedEndOfInput { line, column, .. } => (line, column),
+                }),
+                JsonError::Parse(
+                    ParseError::UnexpectedToken { location, .. }
+                    | ParseError::UnexpectedEnd { location }
+                    | ParseError::TrailingTokens { location, .. }
+                    | ParseError::DepthLimitExceeded { location }
+                    | ParseError::DuplicateKey { location, .. }
+                    | ParseError::ExpectedContainer { location }
+                    | ParseError::ExpectedColon { location, .. }
+                    | ParseError::ExpectedCommaOrRightBrace { location, .. }
+                    | ParseError::ExpectedCommaOrRightBracket { location, .. },
+                ) => location.as_ref().map(|location| (location.line, location.column)),
+                _ => None,
+            }
+        }
+    
+        // moves the position of an error found in a slice that starts `lines` lines and
+        // `offset` bytes into the whole input
+        fn shift(&mut self, lines: usize, offset: usize) {
+            match self {
+                JsonError::Tokenize(
+                    TokenizeError::UnexpectedCharacter { line, .. }
+                    | TokenizeError::InvalidEscape { line, .. }
+                    | TokenizeError::InvalidUnicodeEscape { line, .. }
+                    | TokenizeError::UnpairedSurrogate { line, .. }
+                    | TokenizeError::UnclosedString { line, .. }
+                    | TokenizeError::MalformedNumber { line, .. }
+                    | TokenizeError::LeadingZero { line, .. }
+                    | TokenizeError::UnclosedComment { line, .. }
+                    | TokenizeError::NumberOutOfRange { line, .. }
+                    | TokenizeError::ControlCharacter { line, .. }
+                    | TokenizeError::UnexpectedEndOfInput { line, .. },
+                ) => *line += lines,
+                JsonError::Parse(
+                    ParseError::UnexpectedToken { location: Some(location), .. }
+                    | ParseError::UnexpectedEnd { location: Some(location) }
+                    | ParseError::TrailingTokens { location: Some(location), .. }
+                    | ParseError::DepthLimitExceeded { location: Some(location) }
+                    | ParseError::DuplicateKey { location: Some(location), .. }
+                    | ParseError::ExpectedContainer { location: Some(location) }
+                    | ParseError::ExpectedColon { location: Some(location), .. }
+                    | ParseError::ExpectedCommaOrRightBrace { location: Some(location), .. }
+                    | ParseError::ExpectedCommaOrRightBracket { location: Some(location), .. },
+                ) => {
+                    location.line += lines;
+                    location.span = location.span.start + offset..location.span.end + offset;
+                },
+                _ => {},
+            }
+        }
+    
+        /// Displays the error followed by the line of `input` it points at, with a caret under
+        /// the column. Lines longer than `width` characters are cut down to `width` around the
+        /// caret. Errors without a position display as they are.
+        pub fn with_snippet(&self, input: &str, width: usize) -> Snippet<'_> {
+            let Some((line, column)) = self.line_column() else {
+                return Snippet { error: self, snippet: None };
+            };
+            // positions are counted after the byte order mark parse_str skips
+            let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+            // tabs would throw off the caret
+            let text: Vec<char> = input.lines().nth(line - 1).unwrap_or("").trim_end_matches('\r').chars()
+                .map(|ch| if ch == '\t' { ' ' } else { ch }).collect();
+            let mut caret = column - 1;
+            let (mut start, mut end) = (0, text.len());
+            if text.len() > width {
+                start = caret.saturating_sub(width / 2).min(text.len() - width);
+                end = start + width;
+            }
+            let mut excerpt: String = text[start..end].iter().collect();
+            caret -= start;
+            if start > 0 {
+                excerpt.insert_str(0, "...");
+                caret += 3;
+            }
+            if end < text.len() {
+                excerpt.push_str("...");
+            }
+    
+            let number = line.to_string();
+            let pad = " ".repeat(number.len());
+            let snippet = format!("{} |\n{} | {}\n{} | {}^", pad, number, excerpt, pad, " ".repeat(caret));
+            Snippet { error: self, snippet: Some(snippet) }
+        }
+    }
+    
+    /// A `JsonError` together with an excerpt of the input around it, see `JsonError::with_snippet`.
+    #[derive(Debug)]
+    pub struct Snippet<'e> {
+        error: &'e JsonError,
+        snippet: Option<String>,
+    }
+    
+    impl fmt::Display for Snippet<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match &self.snippet {
+                Some(snippet) => write!(f, "{}\n{}", self.error, snippet),
+                None => write!(f, "{}", self.error),
+            }
+        }
+    }
+    
+    impl fmt::Display for JsonError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                JsonError::Io(err) => write!(f, "io error: {}", err),
+                JsonError::Utf8(err) => write!(f, "invalid utf-8: {}", err),
+                JsonError::Tokenize(err) => write!(f, "{}", err),
+                JsonError::Parse(err) => write!(f, "{}", err),
+            }
+        }
+    }
+    
+    // no source(), since Display already prints the wrapped error and a chain would repeat it
+    impl std::error::Error for JsonError {}
+    
+    impl From<std::io::Error> for JsonError {
+        fn from(err: std::io::Error) -> Self {
+            JsonError::Io(err)
+        }
+    }
+    
+    impl From<std::str::Utf8Error> for JsonError {
+        fn from(err: std::str::Utf8Error) -> Self {
+            JsonError::Utf8(err)
+        }
+    }
+    
+    impl From<TokenizeError> for JsonError {
+        fn from(err: TokenizeError) -> Self {
+            JsonError::Tokenize(err)
+        }
+    }
+    
+    impl From<ParseError> for JsonError {
+        fn from(err: ParseError) -> Self {
+            JsonError::Parse(err)
+        }
+    }
+    
+    
+    /// A leading UTF-8 byte order mark is skipped; ones later in the document are not.
+    pub fn parse_str(input: &str) -> Result<JsonValue, JsonError> {
+        parse_str_with_depth(input, DEFAULT_MAX_DEPTH)
+    }
+    
+    /// Like `parse_str`, but fails with `DepthLimitExceeded` once objects/arrays nest deeper than `max_depth`.
+    /// Parsing doesn't use the call stack, but dropping, cloning, comparing, serializing and
+    /// visiting a value recurse once per level, so a limit far above the default needs a
+    /// correspondingly large stack for those.
+    pub fn parse_str_with_depth(input: &str, max_depth: usize) -> Result<JsonValue, JsonError> {
+        parse_str_with_options(input, &ParserOptions::new().max_depth(max_depth))
+    }
+    
+    /// Like `parse_str`, with both the tokenizer and the parser configured by `options`.
+    pub fn parse_str_with_options(input: &str, options: &ParserOptions) -> Result<JsonValue, JsonError> {
+        if let Some(max) = options.max_input_len.filter(|&max| input.len() > max) {
+            return Err(ParseError::InputTooLarge { len: input.len(), max }.into());
+        }
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let tokens = JsonTokenizer::with_options(input, options.clone()).tokenize_with_spans()?;
+        let (tokens, spans): (Vec<_>, Vec<_>) = tokens.into_iter().unzip();
+        let mut parser = JsonParser::with_options(&tokens, options.clone()).source(input, &spans);
+        let value = parser.parse()?;
+        if let Some(token) = parser.next() {
+            return Err(ParseError::TrailingTokens { token: token.clone(), location: parser.location(parser.position - 1) }.into());
+        }
+        Ok(value)
+    }
+    
+    /// Parses tokens from `tokenize`, possibly rewritten on the way. Without the source text
+    /// errors carry no location.
+    pub fn parse_tokens(tokens: &[JsonToken]) -> Result<JsonValue, ParseError> {
+        let mut parser = JsonParser::new(tokens);
+        let value = parser.parse()?;
+        match parser.next() {
+            Some(token) => Err(ParseError::TrailingTokens { token: token.clone(), location: None }),
+            None => Ok(value),
+        }
+    }
+    
+    pub fn parse_bytes(input: &[u8]) -> Result<JsonValue, JsonError> {
+        parse_str(std::str::from_utf8(input)?)
+    }
+    
+    /// A `JsonValue` whose strings and keys borrow from the parsed text, so reading a document
+    /// only allocates for strings that had escapes to decode.
+    #[derive(Debug, Clone)]
+    pub enum BorrowedValue<'a> {
+        Null,
+        Bool(bool),
+        Number(f64),
+        Integer(i64),
+        UInteger(u64),
+        RawNumber(String),
+        String(Cow<'a, str>),
+        Array(Vec<BorrowedValue<'a>>),
+        Object(Vec<(Cow<'a, str>, BorrowedValue<'a>)>),
+    }
+    
+    /// Objects compare equal regardless of order, like `JsonValue`'s.
+    impl PartialEq for BorrowedValue<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (BorrowedValue::Null, BorrowedValue::Null) => true,
+                (BorrowedValue::Bool(a), BorrowedValue::Bool(b)) => a == b,
+                (BorrowedValue::Number(a), BorrowedValue::Number(b)) => a == b,
+                (BorrowedValue::Integer(a), BorrowedValue::Integer(b)) => a == b,
+                (BorrowedValue::UInteger(a), BorrowedValue::UInteger(b)) => a == b,
+                (BorrowedValue::RawNumber(a), BorrowedValue::RawNumber(b)) => a == b,
+                (BorrowedValue::String(a), BorrowedValue::String(b)) => a == b,
+                (BorrowedValue::Array(a), BorrowedValue::Array(b)) => a == b,
+                (BorrowedValue::Object(a), BorrowedValue::Object(b)) => objects_equal(a, b),
+                _ => false,
+            }
+        }
+    }
+    
+    impl<'a> BorrowedValue<'a> {
+        /// First entry with this key, like `JsonValue::get`.
+        pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+            match self {
+                BorrowedValue::Object(object) => object.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+                _ => None,
+            }
+        }
+    
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                BorrowedValue::String(s) => Some(s),
+                _ => None,
+            }
+        }
+    
+        pub fn into_owned(self) -> JsonValue {
+            match self {
+                BorrowedValue::Null => JsonValue::Null,
+                BorrowedValue::Bool(b) => JsonValue::Bool(b),
+                BorrowedValue::Number(num) => JsonValue::Number(num),
+                BorrowedValue::Integer(num) => JsonValue::Integer(num),
+                BorrowedValue::UInteger(num) => JsonValue::UInteger(num),
+                BorrowedValue::RawNumber(text) => JsonValue::RawNumber(text),
+                BorrowedValue::String(s) => JsonValue::String(s.into_owned()),
+                BorrowedValue::Array(array) => JsonValue::Array(array.into_iter().map(BorrowedValue::into_owned).collect()),
+                BorrowedValue::Object(object) => {
+                    JsonValue::Object(object.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect())
+                },
+            }
+        }
+    }
+    
+    impl<'s> Build<'s> for BorrowedValue<'s> {
+        type Str = Cow<'s, str>;
+        fn string(token: &str, raw: Option<&'s str>) -> Cow<'s, str> {
+            raw.map_or_else(|| Cow::Owned(token.to_string()), Cow::Borrowed)
+        }
+        fn scalar(value: JsonValue) -> Self {
+            match value {
+                JsonValue::Bool(b) => BorrowedValue::Bool(b),
+                JsonValue::Number(num) => BorrowedValue::Number(num),
+                JsonValue::Integer(num) => BorrowedValue::Integer(num),
+                JsonValue::UInteger(num) => BorrowedValue::UInteger(num),
+                JsonValue::RawNumber(text) => BorrowedValue::RawNumber(text),
+                _ => BorrowedValue::Null,
+            }
+        }
+        fn from_string(s: Cow<'s, str>) -> Self {
+            BorrowedValue::String(s)
+        }
+        fn array(array: Vec<Self>) -> Self {
+            BorrowedValue::Array(array)
+        }
+        fn object(object: Vec<(Cow<'s, str>, Self)>) -> Self {
+            BorrowedValue::Object(object)
+        }
+    }
+    
+    /// Like `parse_str`, keeping strings as slices of `input` where possible.
+    pub fn parse_str_borrowed(input: &str) -> Result<BorrowedValue<'_>, JsonError> {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let mut tokenizer = JsonTokenizer::new(input);
+        tokenizer.skip_plain_strings = true;
+        let (tokens, spans): (Vec<_>, Vec<_>) = tokenizer.tokenize_with_spans()?.into_iter().unzip();
+        let mut parser = JsonParser::new(&tokens).source(input, &spans);
+        let value = parser.build(Some(input))?;
+        if parser.next().is_some() {
+            let index = parser.position - 1;
+            return Err(ParseError::TrailingTokens { token: parser.token(index), location: parser.location(index) }.into());
+        }
+        Ok(value)
+    }
+    
+    /// Parses `input` and serializes it back compactly. Running the output through again gives
+    /// the same string, which makes this handy for checking the parser and serializer agree;
+    /// the first pass normalizes whitespace, escapes and number formatting (`1.0` becomes `1`).
+    pub fn roundtrip(input: &str) -> Result<String, JsonError> {
+        Ok(parse_str(input)?.to_string())
+    }
+    
+    /// Checks that `input` is well-formed JSON, failing with the same error `parse_str` would.
+    pub fn validate(input: &str) -> Result<(), JsonError> {
+        // the borrowing parser skips copying strings, which is most of the allocation
+        parse_str_borrowed(input).map(|_| ())
+    }
+    
+    // reads the tokens of the value that starts with `first`, up to the bracket that closes
+    // it, leaving anything after that value untokenized
+    fn value_tokens(tokenizer: &mut JsonTokenizer, first: (JsonToken, Range<usize>)) -> Result<(Vec<JsonToken>, Vec<Range<usize>>), TokenizeError> {
+        let (mut token, mut span) = first;
+        let (mut tokens, mut spans) = (Vec::new(), Vec::new());
+        let mut depth = 0;
+        loop {
+            match token {
+                JsonToken::LeftBrace | JsonToken::LeftBracket => depth += 1,
+                JsonToken::RightBrace | JsonToken::RightBracket if depth > 0 => depth -= 1,
+                _ => {},
+            }
+            tokens.push(token);
+            spans.push(span);
+            if depth == 0 {
+                return Ok((tokens, spans));
+            }
+            // if the input ends first, the parser reports whatever it runs into
+            match tokenizer.next_token()? {
+                Some(next) => (token, span) = next,
+                None => return Ok((tokens, spans)),
+            }
+        }
+    }
+    
+    // yields the elements of a top-level array one at a time, see parse_array_stream
+    struct ArrayStream<'a> {
+        input: &'a str,
+        tokenizer: JsonTokenizer<'a>,
+        // the opening '[' was read
+        started: bool,
+        // the closing ']' was read
+        closed: bool,
+        done: bool,
+    }
+    
+    impl ArrayStream<'_> {
+        fn unexpected(&self, token: JsonToken, span: Range<usize>) -> JsonError {
+            ParseError::UnexpectedToken { token, location: Some(Location::new(self.input, span)) }.into()
+        }
+    
+        fn unexpected_end(&self) -> JsonError {
+            let end = self.input.len();
+            ParseError::UnexpectedEnd { location: Some(Location::new(self.input, end..end)) }.into()
+        }
+    
+        fn finish(&mut self) -> Result<Option<JsonValue>, JsonError> {
+            match self.tokenizer.next_token()? {
+                Some((token, span)) => Err(ParseError::TrailingTokens { token, location: Some(Location::new(self.input, span)) }.into()),
+                None => Ok(None),
+            }
+        }
+    
+        fn next_element(&mut self) -> Result<Option<JsonValue>, JsonError> {
+            if self.closed {
+                return self.finish();
+            }
+            let first = !self.started;
+            if first {
+                self.started = true;
+                match self.tokenizer.next_token()? {
+                    Some((JsonToken::LeftBracket, _)) => {},
+                    Some((token, span)) => return Err(self.unexpected(token, span)),
+                    None => return Err(ParseError::EmptyInput.into()),
+                }
+            }
+    
+            let (token, span) = self.tokenizer.next_token()?.ok_or_else(|| self.unexpected_end())?;
+            if token == JsonToken::RightBracket {
+                if first {
+                    self.closed = true;
+                    return self.finish();
+                }
+                return Err(self.unexpected(token, span));
+            }
+    
+            let (tokens, spans) = value_tokens(&mut self.tokenizer, (token, span))?;
+            // the surrounding array counts towards the depth limit, as it does in parse_str
+            let value = JsonParser::with_options(&tokens, ParserOptions::new().max_depth(DEFAULT_MAX_DEPTH - 1)).source(self.input, &spans).parse()?;
+    
+            match self.tokenizer.next_token()? {
+                Some((JsonToken::Comma, _)) => {},
+                Some((JsonToken::RightBracket, _)) => self.closed = true,
+                Some((token, span)) => {
+                    return Err(ParseError::ExpectedCommaOrRightBracket { token, location: Some(Location::new(self.input, span)) }.into());
+                },
+                None => return Err(self.unexpected_end()),
+            }
+            Ok(Some(value))
+        }
+    }
+    
+    impl Iterator for ArrayStream<'_> {
+        type Item = Result<JsonValue, JsonError>;
+    
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.done {
+                return None;
+            }
+            let element = self.next_element();
+            // nothing more comes after the end of the array or an error
+            self.done = !matches!(element, Ok(Some(_)));
+            element.transpose()
+        }
+    }
+    
+    /// Parses a top-level array one element at a time, e.g. a big `[{...}, {...}, ...]` file
+    /// of records. Only the element being parsed and its tokens are held in memory, where
+    /// `parse_str` keeps the whole token stream and the whole tree. The first error ends the
+    /// iteration; elements yielded before it were already valid.
+    pub fn parse_array_stream(input: &str) -> impl Iterator<Item = Result<JsonValue, JsonError>> + '_ {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        ArrayStream { input, tokenizer: JsonTokenizer::new(input), started: false, closed: false, done: false }
+    }
+    
+    /// Parses the JSON value at the start of `input` and returns it with the number of bytes
+    /// it took up, for a document embedded in a larger stream: whatever follows at
+    /// `input[consumed..]` is left alone instead of failing with `TrailingTokens`.
+    pub fn parse_prefix(input: &str) -> Result<(JsonValue, usize), JsonError> {
+        let bom = if input.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+        let input = &input[bom..];
+        let mut tokenizer = JsonTokenizer::new(input);
+        let first = tokenizer.next_token()?.ok_or(ParseError::EmptyInput)?;
+        let (tokens, spans) = value_tokens(&mut tokenizer, first)?;
+        let value = JsonParser::new(&tokens).source(input, &spans).parse()?;
+        Ok((value, bom + spans.last().map_or(0, |span| span.end)))
+    }
+    
+    // a token the tokenizer couldn't read stays in the stream as `None`, standing in for the
+    // value it was meant to be, so the parser doesn't report the same mistake a second time
+    type RecoveredToken = (Option<JsonToken>, Range<usize>);
+    
+    fn tokenize_recovering(input: &str, errors: &mut Vec<JsonError>) -> Vec<RecoveredToken> {
+        let mut tokenizer = JsonTokenizer::new(input);
+        let mut tokens = Vec::new();
+        loop {
+            let before = tokenizer.position;
+            match tokenizer.next_token() {
+                Ok(Some((token, span))) => tokens.push((Some(token), span)),
+                Ok(None) => return tokens,
+                Err(err) => {
+                    errors.push(err.into());
+                    let start = input[before..].find(|ch| !is_whitespace(ch)).map_or(input.len(), |i| before + i);
+                    let rest = &input[start..];
+                    // carry on after the rest of a bad string, which can't span lines, or else
+                    // at the next delimiter
+                    let len = if rest.starts_with('"') {
+                        let mut escaped = false;
+                        let end = rest.char_indices().skip(1).find(|&(_, ch)| {
+                            let end = !escaped && (ch == '"' || ch == '\n');
+                            escaped = !escaped && ch == '\\';
+                            end
+                        });
+                        end.map_or(rest.len(), |(i, ch)| if ch == '"' { i + 1 } else { i })
+                    } else {
+                        let end = rest.char_indices().skip(1).find(|&(_, ch)| is_whitespace(ch) || "\",:[]{}".contains(ch));
+                        end.map_or(rest.len(), |(i, _)| i)
+                    };
+                    tokenizer.position = start + len;
+                    tokens.push((None, start..start + len));
+                },
+            }
+        }
+    }
+    
+    // a recursive descent parser that notes each error and resyncs at the next `,`, `]` or
+    // `}` instead of stopping, see parse_str_collect_errors
+    struct RecoveringParser<'a> {
+        input: &'a str,
+        tokens: Vec<RecoveredToken>,
+        position: usize,
+        errors: Vec<JsonError>,
+    }
+    
+    impl RecoveringParser<'_> {
+        fn at(&self, token: &JsonToken) -> bool {
+            matches!(self.tokens.get(self.position), Some((Some(t), _)) if t == token)
+        }
+    
+        fn unexpected(&mut self) {
+            let (token, span) = &self.tokens[self.position];
+            let location = Some(Location::new(self.input, span.clone()));
+            if let Some(token) = token.clone() {
+                self.errors.push(ParseError::UnexpectedToken { token, location }.into());
+            }
+        }
+    
+        fn unexpected_end(&mut self) {
+            // every container still open runs into the end, but once is enough to say so
+            if matches!(self.errors.last(), Some(JsonError::Parse(ParseError::UnexpectedEnd { .. }))) {
+                return;
+            }
+            let end = self.input.len();
+            self.errors.push(ParseError::UnexpectedEnd { location: Some(Location::new(self.input, end..end)) }.into());
+        }
+    
+        // moves up to the next comma or closing token of the current container
+        fn skip_to_separator(&mut self) {
+            let mut depth = 0;
+            while let Some((token, _)) = self.tokens.get(self.position) {
+                match token {
+                    Some(JsonToken::LeftBrace | JsonToken::LeftBracket) => depth += 1,
+                    Some(JsonToken::RightBrace | JsonToken::RightBracket | JsonToken::Comma) if depth == 0 => return,
+                    Some(JsonToken::RightBrace | JsonToken::RightBracket) => depth -= 1,
+                    _ => {},
+                }
+                self.position += 1;
+            }
+        }
+    
+        fn value(&mut self, depth: usize) -> Option<JsonValue> {
+            let Some((token, span)) = self.tokens.get(self.position).cloned() else {
+                self.unexpected_end();
+                return None;
+            };
+            let Some(token) = token else {
+                // already reported by the tokenizer
+                self.position += 1;
+                return None;
+            };
+            let value = match token {
+                JsonToken::Null => JsonValue::Null,
+                JsonToken::True => JsonValue::Bool(true),
+                JsonToken::False => JsonValue::Bool(false),
+                JsonToken::Number(num) => JsonValue::Number(num),
+                JsonToken::Integer(num) => JsonValue::Integer(num),
+                JsonToken::UInteger(num) => JsonValue::UInteger(num),
+                JsonToken::RawNumber(text) => JsonValue::RawNumber(text),
+                JsonToken::String(s) => JsonValue::String(s),
+                JsonToken::LeftBrace | JsonToken::LeftBracket if depth >= DEFAULT_MAX_DEPTH => {
+                    self.errors.push(ParseError::DepthLimitExceeded { location: Some(Location::new(self.input, span)) }.into());
+                    // skip the whole container, up to its matching closing token
+                    let mut open = 0;
+                    while let Some((token, _)) = self.tokens.get(self.position) {
+                        self.position += 1;
+                        match token {
+                            Some(JsonToken::LeftBrace | JsonToken::LeftBracket) => open += 1,
+                            Some(JsonToken::RightBrace | JsonToken::RightBracket) => {
+                                open -= 1;
+                                if open == 0 {
+                                    break;
+                                }
+                            },
+                            _ => {},
+                        }
+                    }
+                    return None;
+                },
+                JsonToken::LeftBrace => {
+                    self.position += 1;
+                    return Some(self.object(depth + 1));
+                },
+                JsonToken::LeftBracket => {
+                    self.position += 1;
+                    return Some(self.array(depth + 1));
+                },
+                JsonToken::Colon => {
+                    self.unexpected();
+                    self.position += 1;
+                    return None;
+                },
+                // left for the enclosing container to resync on
+                JsonToken::Comma | JsonToken::RightBrace | JsonToken::RightBracket => {
+                    self.unexpected();
+                    return None;
+                },
+            };
+            self.position += 1;
+            Some(value)
+        }
+    
+        // after an element: true if another one follows, false once the container is done
+        fn separator(&mut self, close: JsonToken) -> bool {
+            loop {
+                let Some((token, span)) = self.tokens.get(self.position).cloned() else {
+                    self.unexpected_end();
+                    return false;
+                };
+                match token {
+                    Some(JsonToken::Comma) => {
+                        self.position += 1;
+                        return true;
+                    },
+                    Some(token) if token == close => {
+                        self.position += 1;
+                        return false;
+                    },
+                    None => self.position += 1,
+                    Some(token) => {
+                        let location = Some(Location::new(self.input, span));
+                        self.errors.push(match close {
+                            JsonToken::RightBrace => ParseError::ExpectedCommaOrRightBrace { token: token.clone(), location },
+                            _ => ParseError::ExpectedCommaOrRightBracket { token: token.clone(), location },
+                        }.into());
+                        // a mismatched closing token is most likely closing an outer container
+                        if matches!(token, JsonToken::RightBrace | JsonToken::RightBracket) {
+                            return false;
+                        }
+                        self.skip_to_separator();
+                    },
+                }
+            }
+        }
+    
+        fn array(&mut self, depth: usize) -> JsonValue {
+            let mut array = Vec::new();
+            if self.at(&JsonToken::RightBracket) {
+                self.position += 1;
+                return JsonValue::Array(array);
+            }
+            loop {
+                array.extend(self.value(depth));
+                if !self.separator(JsonToken::RightBracket) {
+                    return JsonValue::Array(array);
+                }
+            }
+        }
+    
+        fn object(&mut self, depth: usize) -> JsonValue {
+            let mut object = Vec::new();
+            if self.at(&JsonToken::RightBrace) {
+                self.position += 1;
+                return JsonValue::Object(object);
+            }
+            loop {
+                match self.tokens.get(self.position).cloned() {
+                    Some((Some(JsonToken::String(key)), _)) => {
+                        self.position += 1;
+                        let colon = self.at(&JsonToken::Colon);
+                        match self.tokens.get(self.position).cloned() {
+                            Some((Some(JsonToken::Colon), _)) => self.position += 1,
+                            Some((Some(token), span)) => {
+                                let location = Some(Location::new(self.input, span));
+                                self.errors.push(ParseError::ExpectedColon { token, location }.into());
+                            },
+                            _ => {},
+                        }
+                        // with the colon missing, `{"a" 1}` still reads the value, while
+                        // `{"a", ...}` has none to read
+                        if colon || (!self.at(&JsonToken::Comma) && !self.at(&JsonToken::RightBrace)) {
+                            if let Some(value) = self.value(depth) {
+                                object.push((key, value));
+                            }
+                        }
+                    },
+                    Some(_) => {
+                        self.unexpected();
+                        self.skip_to_separator();
+                    },
+                    None => {
+                        self.unexpected_end();
+                        return JsonValue::Object(object);
+                    },
+                }
+                if !self.separator(JsonToken::RightBrace) {
+                    return JsonValue::Object(object);
+                }
+            }
+        }
+    }
+    
+    /// Like `parse_str`, but keeps going after an error to report as many as it can in one
+    /// pass, e.g. for an editor. Errors come sorted by position. The value is whatever could
+    /// be recovered, with the broken parts left out; it's only complete when there were no
+    /// errors, and `None` when not even the start of one was found.
+    pub fn parse_str_collect_errors(input: &str) -> (Option<JsonValue>, Vec<JsonError>) {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let mut errors = Vec::new();
+        let tokens = tokenize_recovering(input, &mut errors);
+        if tokens.is_empty() {
+            return (None, vec![ParseError::EmptyInput.into()]);
+        }
+        let mut parser = RecoveringParser { input, tokens, position: 0, errors };
+        let value = parser.value(0);
+        // just the first leftover token, the rest of them are most likely more of the same
+        let trailing = parser.tokens[parser.position.min(parser.tokens.len())..].iter().find(|(token, _)| token.is_some());
+        if let Some((Some(token), span)) = trailing {
+            let location = Some(Location::new(input, span.clone()));
+            parser.errors.push(ParseError::TrailingTokens { token: token.clone(), location }.into());
+        }
+        let mut errors = parser.errors;
+        errors.sort_by_key(JsonError::line_column);
+        (value, errors)
+    }
+    
+    /// Parses newline-delimited JSON (JSON Lines), one document per line. Blank lines are
+    /// skipped, and a line that fails to parse yields its error without stopping the rest.
+    /// Error locations point into `input` as a whole, so they name the failing line.
+    pub fn parse_lines(input: &str) -> impl Iterator<Item = Result<JsonValue, JsonError>> + '_ {
+        let mut offset = 0;
+        input.split_inclusive('\n').enumerate().filter_map(move |(index, line)| {
+            let start = offset;
+            offset += line.len();
+            if line.chars().all(is_whitespace) {
+                return None;
+            }
+            // without its line break, so an unclosed document ends on its own line
+            Some(parse_str(line.trim_end_matches(['\n', '\r'])).map_err(|mut err| {
+                err.shift(index, start);
+                err
+            }))
+        })
+    }
+    
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum JsonEvent {
+        StartObject,
+        EndObject,
+        StartArray,
+        EndArray,
+        Key(String),
+        String(String),
+        Number(f64),
+        Integer(i64),
+        UInteger(u64),
+        /// Only with the `arbitrary_precision` feature, as in `JsonValue::RawNumber`.
+        RawNumber(String),
+        Bool(bool),
+        Null,
+    }
+    
+    /// Reports the document to `callback` as a stream of events instead of building a
+    /// `JsonValue`. Events already sent stay sent when a later part of the input is invalid.
+    pub fn parse_events<F: FnMut(JsonEvent)>(input: &str, mut callback: F) -> Result<(), JsonError> {
+        #[derive(PartialEq)]
+        enum Expect {
+            Value,
+            Key,
+            Colon,
+            // a comma or closing token, or nothing at all once the root value is complete
+            Separator,
+        }
+    
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        // tokens are pulled one at a time, so neither the token stream nor the document is
+        // ever held in memory, and events go out before later parts of the input are read
+        let mut tokenizer = JsonTokenizer::new(input);
+        let mut empty = true;
+        let mut expect = Expect::Value;
+        // open containers, true for objects
+        let mut stack: Vec<bool> = Vec::new();
+        let mut just_opened = false;
+        while let Some((token, span)) = tokenizer.next_token()? {
+            empty = false;
+            // only built on the error paths, as it scans the input up to `span`
+            let location = || Some(Location::new(input, span.clone()));
+            let opened = just_opened;
+            just_opened = false;
+            match expect {
+                Expect::Value => match token {
+                    JsonToken::LeftBrace => {
+                        callback(JsonEvent::StartObject);
+                        stack.push(true);
+                        just_opened = true;
+                        expect = Expect::Key;
+                    },
+                    JsonToken::LeftBracket => {
+                        callback(JsonEvent::StartArray);
+                        stack.push(false);
+                        just_opened = true;
+                    },
+                    JsonToken::RightBracket if opened => {
+                        callback(JsonEvent::EndArray);
+                        stack.pop();
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::String(s) => {
+                        callback(JsonEvent::String(s));
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::Number(num) => {
+                        callback(JsonEvent::Number(num));
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::Integer(num) => {
+                        callback(JsonEvent::Integer(num));
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::UInteger(num) => {
+                        callback(JsonEvent::UInteger(num));
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::RawNumber(text) => {
+                        callback(JsonEvent::RawNumber(text));
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::True | JsonToken::False => {
+                        callback(JsonEvent::Bool(token == JsonToken::True));
+                        expect = Expect::Separator;
+                    },
+                    JsonToken::Null => {
+                        callback(JsonEvent::Null);
+                        expect = Expect::Separator;
+                    },
+                    _ => return Err(ParseError::UnexpectedToken { token, location: location() }.into()),
+                },
+                Expect::Key => match token {
+                    JsonToken::String(key) => {
+                        callback(JsonEvent::Key(key));
+                        expect = Expect::Colon;
+                    },
+                    JsonToken::RightBrace if opened => {
+                        callback(JsonEvent::EndObject);
+                        stack.pop();
+                        expect = Expect::Separator;
+                    },
+                    _ => return Err(ParseError::UnexpectedToken { token, location: location() }.into()),
+                },
+                Expect::Colon => match token {
+                    JsonToken::Colon => expect = Expect::Value,
+                    _ => return Err(ParseError::ExpectedColon { token, location: location() }.into()),
+                },
+                Expect::Separator => {
+                    match (stack.last(), token) {
+                        (None, token) => return Err(ParseError::TrailingTokens { token, location: location() }.into()),
+                        (Some(true), JsonToken::Comma) => expect = Expect::Key,
+                        (Some(false), JsonToken::Comma) => expect = Expect::Value,
+                        (Some(true), JsonToken::RightBrace) => {
+                            callback(JsonEvent::EndObject);
+                            stack.pop();
+                        },
+                        (Some(false), JsonToken::RightBracket) => {
+                            callback(JsonEvent::EndArray);
+                            stack.pop();
+                        },
+                        (Some(true), token) => return Err(ParseError::ExpectedCommaOrRightBrace { token, location: location() }.into()),
+                        (Some(false), token) => return Err(ParseError::ExpectedCommaOrRightBracket { token, location: location() }.into()),
+                    }
+                },
+            }
+        }
+        if empty {
+            Err(ParseError::EmptyInput.into())
+        } else if expect == Expect::Separator && stack.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::UnexpectedEnd { location: Some(Location::new(input, input.len()..input.len())) }.into())
+        }
+    }
+    
+    /// Reads `reader` to the end and parses what it produced, e.g. stdin or an `io::Cursor`.
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> Result<JsonValue, JsonError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        parse_bytes(&bytes)
+    }
+    
+    use std::fs::read_to_string;
+    pub fn load_from_file(path: &str) -> Result<JsonValue, JsonError> {
+        let file_data = read_to_string(path)?;
+        parse_str(&file_data)
+    }
+    
+    pub fn save_to_file(path: &str, value: &JsonValue) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        to_writer(&mut writer, value)?;
+        std::io::Write::flush(&mut writer)
+    }
+    
+    pub fn save_to_file_pretty(path: &str, value: &JsonValue, indent: usize) -> std::io::Result<()> {
+        std::fs::write(path, to_string_pretty(value, indent))
     }
 }
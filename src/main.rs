@@ -2,6 +2,8 @@ use json_parser::parse;
 
 
 fn main() {
-    let json = parse::load_from_file("tree.json");
-    println!("{:#?}", json);
+    match parse::load_from_file("tree.json") {
+        Ok(json) => println!("{:#?}", json),
+        Err(err) => eprintln!("{}", err),
+    }
 }